@@ -1,81 +1,56 @@
-use std::error::Error;
-use std::io::Write;
-use crate::interval::Interval;
+use std::hash::{Hash, Hasher};
+use std::ops::RangeBounds;
+use crate::discrete::Discrete;
+use crate::interval::{Interval, IntervalError};
 use crate::traits::IntervalTree;
 
-struct AVLNode<T: num::PrimInt + std::fmt::Display> {
+mod iter;
+mod map;
+mod set_ops;
+
+pub use iter::{Iter, IntoIter, Range, ClippedRange, Values, Gaps, MapIter};
+pub use map::AVLIntervalMap;
+
+/// A node storing an interval and the value associated with it.
+///
+/// Sets store `()` as value.
+#[derive(Clone, Debug)]
+struct AVLNode<T: Discrete, V = ()> {
     height: i32,
+    /// Number of values covered by the subtree rooted at this node.
+    covered: u128,
     interval: Interval<T>,
-    left: Option<Box<AVLNode<T>>>,
-    right: Option<Box<AVLNode<T>>>
+    value: V,
+    left: Option<Box<AVLNode<T, V>>>,
+    right: Option<Box<AVLNode<T, V>>>
 }
 
 pub enum AVLCase {
     LeftLeft, LeftRight, RightRight, RightLeft, Balanced
 }
 
-impl<T: num::PrimInt + std::fmt::Display> AVLNode<T> {
+impl<T: Discrete> AVLNode<T> {
     fn with_value(interval: Interval<T>) -> Self {
-        Self{height: 1, left: None, right: None, interval}
-    }
-
-    fn merge_down(&mut self) {
-        let mut interval = self.interval;
-        // left
-        if let Some(left_child) = &mut self.left {
-            let _ = left_child.merge_down_helper(
-                &mut interval,
-                &|node| node.left.as_mut(),
-                &|node| node.right.as_mut(),
-            );
-        }
-        // right
-        if let Some(right_child) = &mut self.right {
-            let _ = right_child.merge_down_helper(
-                &mut interval,
-                &|node| node.right.as_mut(),
-                &|node| node.left.as_mut(),
-            );
-        }
-        self.interval = interval;
-        self.maybe_drop_children();
-        self.recompute_height();
-        self.balance_after_deletion();
+        Self::with_entry(interval, ())
     }
 
-    fn merge_down_helper<F1, F3>(&mut self,
-                                 interval: &mut Interval<T>,
-                                 main_side_getter: &F1,
-                                 other_side_getter: &F3) -> bool
-    where
-        F1: Fn(&mut Self) -> Option<&mut Box<Self>>,
-        F3: Fn(&mut Self) -> Option<&mut Box<Self>>
-    {
-        let mut done_merging = false;
-        if let Some(child) = (main_side_getter)(self) {
-            done_merging = child.merge_down_helper(interval,
-                                                   main_side_getter,
-                                                   other_side_getter);
+    /// Build a balanced tree from sorted, disjoint and non-adjacent intervals.
+    fn from_sorted(intervals: &[Interval<T>]) -> Option<Box<Self>> {
+        if intervals.is_empty() {
+            return None;
         }
-        if !done_merging {
-            if interval.can_merge_with(&self.interval) {
-                self.height = -1;      // Mark for deletion
-                interval.merge_inplace_unchecked(&self.interval);
-                if let Some(other_child) = (other_side_getter)(self) {
-                    done_merging = other_child.merge_down_helper(interval,
-                                                                 main_side_getter,
-                                                                 other_side_getter);
-                }
-            } else {
-                done_merging = true;
-            }
-        }
-        if done_merging {
-            self.maybe_drop_children();
-            self.recompute_height();
-            self.balance_after_deletion();
-        }
-        done_merging
+        let middle = intervals.len() / 2;
+        let mut node = Self::with_value(intervals[middle]);
+        node.left = Self::from_sorted(&intervals[..middle]);
+        node.right = Self::from_sorted(&intervals[middle + 1..]);
+        node.recompute_metadata();
+        Some(node.into())
+    }
+}
+
+impl<T: Discrete, V> AVLNode<T, V> {
+    fn with_entry(interval: Interval<T>, value: V) -> Self {
+        Self{height: 1, covered: interval.size(), left: None, right: None, interval, value}
     }
 
     fn balance_after_insertion(&mut self, inserted_interval: Interval<T>) {
@@ -130,14 +105,14 @@ impl<T: num::PrimInt + std::fmt::Display> AVLNode<T> {
                 self.rotate_right();
             }
             AVLCase::LeftRight => {
-                self.rotate_left();
+                self.left.as_mut().expect("AVL Tree broken").rotate_left();
                 self.rotate_right();
             }
             AVLCase::RightRight => {
                 self.rotate_left();
             }
             AVLCase::RightLeft => {
-                self.rotate_right();
+                self.right.as_mut().expect("AVL Tree broken").rotate_right();
                 self.rotate_left();
             },
             AVLCase::Balanced => {}
@@ -147,27 +122,19 @@ impl<T: num::PrimInt + std::fmt::Display> AVLNode<T> {
     fn rotate_left(&mut self) {
         let mut y = *self.right.take().expect("AVL Tree broken");
         self.right = y.left.take();
-        let mut temp = Self::with_value(
-            Interval::new(T::zero(), T::zero())
-        );
-        std::mem::swap(&mut temp, self);
-        y.right.replace(temp.into());
+        self.recompute_metadata();
         std::mem::swap(self, &mut y);
-        y.recompute_height();
-        self.recompute_height();
+        self.left.replace(y.into());
+        self.recompute_metadata();
     }
 
     fn rotate_right(&mut self) {
         let mut y = *self.left.take().expect("AVL Tree broken");
         self.left = y.right.take();
-        let mut temp = Self::with_value(
-            Interval::new(T::zero(), T::zero())
-        );
-        std::mem::swap(&mut temp, self);
-        y.right.replace(temp.into());
+        self.recompute_metadata();
         std::mem::swap(self, &mut y);
-        y.recompute_height();
-        self.recompute_height();
+        self.right.replace(y.into());
+        self.recompute_metadata();
     }
 
     fn balance_score(&self) -> i32 {
@@ -190,230 +157,549 @@ impl<T: num::PrimInt + std::fmt::Display> AVLNode<T> {
         self.right.as_ref().map_or(0, |node| node.height)
     }
 
-    fn recompute_height(&mut self) {
+    fn left_child_covered(&self) -> u128 {
+        self.left.as_ref().map_or(0, |node| node.covered)
+    }
+
+    fn right_child_covered(&self) -> u128 {
+        self.right.as_ref().map_or(0, |node| node.covered)
+    }
+
+    /// Recompute the height and covered count from the children.
+    fn recompute_metadata(&mut self) {
         self.height = self.left_child_height()
             .max(self.right_child_height()) + 1;
+        self.covered = self.left_child_covered()
+            .saturating_add(self.interval.size())
+            .saturating_add(self.right_child_covered());
     }
 
-    fn maybe_drop_children(&mut self) {
-        if self.left_child_height() < 0 {
-            self.left.take();
-        }
-        if self.right_child_height() < 0 {
-            self.right.take();
+    /// Drop the children marked for deletion, and return
+    /// the entry of the dropped child, if any.
+    fn maybe_drop_children(&mut self) -> Option<(Interval<T>, V)> {
+        let left = if self.left_child_height() < 0 {
+            self.left.take()
+        } else {
+            None
+        };
+        let right = if self.right_child_height() < 0 {
+            self.right.take()
+        } else {
+            None
+        };
+        left.or(right).map(|node| (node.interval, node.value))
+    }
+
+    fn is_avl(&self) -> bool {
+        let balance = self.balance_score();
+        (-1..=1).contains(&balance)
+    }
+
+    fn tree_is_avl(&self) -> bool {
+        self.is_avl()
+            && self.height == self.left_child_height().max(self.right_child_height()) + 1
+            && self.left.as_ref().is_none_or(|node| node.tree_is_avl())
+            && self.right.as_ref().is_none_or(|node| node.tree_is_avl())
+    }
+}
+
+impl<T: Discrete, V> AVLNode<T, V> {
+    /// Insert an interval which can not be merged with any interval
+    /// stored in this subtree.
+    fn insert(&mut self, new_interval: Interval<T>, value: V) {
+        let child = if new_interval.is_left_of(&self.interval) {
+            &mut self.left
+        } else {
+            &mut self.right
+        };
+        match child {
+            None => { child.replace(Self::with_entry(new_interval, value).into()); }
+            Some(node) => { node.insert(new_interval, value); }
         }
+        self.recompute_metadata();
+        self.balance_after_insertion(new_interval);
+    }
+
+    /// Remove the entry stored in this node from the tree, and return it.
+    ///
+    /// If this node is a leaf, it is marked for deletion by its parent,
+    /// which takes the entry when dropping it.
+    fn delete(&mut self) -> Option<(Interval<T>, V)> {
+        let node = match (self.left.as_mut(), self.right.as_mut()) {
+            (None, None) => {
+                self.height = -1;   // mark for deletion by parent
+                return None;
+            },
+            (Some(_), None) => {
+                let child = self.left.take().expect("AVL broken");
+                std::mem::replace(self, *child)
+            },
+            (None, Some(_)) => {
+                let child = self.right.take().expect("AVL broken");
+                std::mem::replace(self, *child)
+            },
+            (Some(_), Some(_)) => {
+                let (interval, value) = self.get_and_delete_successor();
+                let interval = std::mem::replace(&mut self.interval, interval);
+                let value = std::mem::replace(&mut self.value, value);
+                return Some((interval, value));
+            }
+        };
+        Some((node.interval, node.value))
     }
 
-    fn get_and_delete_successor(&mut self) -> Interval<T> {
+    fn get_and_delete_successor(&mut self) -> (Interval<T>, V) {
         let node = self.right.as_mut().expect(
             "get_and_delete_successor() called without right node"
         );
-        Self::get_and_delete_successor_helper(node)
+        let entry = Self::get_and_delete_successor_helper(node);
+        let dropped = self.maybe_drop_children();
+        entry.or(dropped).expect("successor not deleted")
     }
 
-    fn get_and_delete_successor_helper(node: &mut Self) -> Interval<T> {
+    /// Returns `None` if `node` has been marked for deletion,
+    /// in which case its parent takes the entry.
+    fn get_and_delete_successor_helper(node: &mut Self) -> Option<(Interval<T>, V)> {
         if let Some(child) = node.left.as_mut() {
-            let interval = Self::get_and_delete_successor_helper(child);
-            node.maybe_drop_children();
-            node.recompute_height();
+            let entry = Self::get_and_delete_successor_helper(child);
+            let dropped = node.maybe_drop_children();
+            node.recompute_metadata();
             node.balance_after_deletion();
-            interval
+            entry.or(dropped)
         } else {
-            node.height = -1;
-            node.interval
+            match node.right.take() {
+                None => {
+                    node.height = -1;       // mark for deletion by parent
+                    None
+                },
+                Some(child) => {
+                    let node = std::mem::replace(node, *child);
+                    Some((node.interval, node.value))
+                }
+            }
         }
     }
 
-    fn print_tree<W: std::io::Write>(&self,
-                                     writer: &mut W,
-                                     indent: usize) -> Result<(), Box<dyn std::error::Error>> {
+    /// Remove the first entry encountered on the search path of `probe`
+    /// for which `matches` holds, and return it.
+    ///
+    /// `matches` must only hold for intervals overlapping `probe` or
+    /// for the nearest intervals on either side of it, which all
+    /// lie on its search path.
+    fn delete_matching<F>(&mut self,
+                          probe: &Interval<T>,
+                          matches: &F) -> Option<(Interval<T>, V)>
+    where
+        F: Fn(&Interval<T>, &V) -> bool
+    {
+        let deleted = if matches(&self.interval, &self.value) {
+            self.delete()
+        } else if probe.is_left_of(&self.interval) {
+            self.left
+                .as_mut()
+                .and_then(|node| node.delete_matching(probe, matches))
+        } else {
+            self.right
+                .as_mut()
+                .and_then(|node| node.delete_matching(probe, matches))
+        };
+        // A deleted leaf child is only marked, and handed over here.
+        let deleted = deleted.or_else(|| self.maybe_drop_children());
+        if deleted.is_some() {
+            self.recompute_metadata();
+            self.balance_after_deletion();
+        }
+        deleted
+    }
+
+    /// Return the node whose interval contains all of `interval`, if any.
+    fn find_containing(&self, interval: &Interval<T>) -> Option<&Self> {
+        if self.interval.contains_interval(interval) {
+            Some(self)
+        } else if interval.is_left_of(&self.interval) {
+            self.left
+                .as_ref()
+                .and_then(|node| node.find_containing(interval))
+        } else {
+            self.right
+                .as_ref()
+                .and_then(|node| node.find_containing(interval))
+        }
+    }
+
+    /// Number of covered values strictly smaller than `value` in this subtree.
+    fn covered_before(&self, value: T) -> u128 {
+        let mut node = Some(self);
+        let mut covered = 0u128;
+        while let Some(current) = node {
+            if value <= current.interval.start() {
+                node = current.left.as_deref();
+                continue;
+            }
+            covered = covered.saturating_add(current.left_child_covered());
+            if current.interval.contains_value(value) {
+                let before = T::steps_between(&current.interval.start(), &value);
+                return covered.saturating_add(before);
+            }
+            covered = covered.saturating_add(current.interval.size());
+            node = current.right.as_deref();
+        }
+        covered
+    }
+
+    /// Return the `k`-th smallest covered value in this subtree, counting from zero.
+    fn select(&self, k: u128) -> Option<T> {
+        let mut node = Some(self);
+        let mut k = k;
+        while let Some(current) = node {
+            let left_covered = current.left_child_covered();
+            if k < left_covered {
+                node = current.left.as_deref();
+                continue;
+            }
+            k -= left_covered;
+            let size = current.interval.size();
+            if k < size {
+                return current.interval.nth(k);
+            }
+            k -= size;
+            node = current.right.as_deref();
+        }
+        None
+    }
+
+    fn tree_size(&self) -> i32 {
+        let left_size = self.left
+            .as_ref()
+            .map_or(0, |n| n.tree_size());
+        let right_size = self.right
+            .as_ref()
+            .map_or(0, |n| n.tree_size());
+        left_size + right_size + 1
+    }
+
+    /// Remove the first entry on the search path of `probe` for which
+    /// `matches` holds from the tree rooted at `root`, and return it.
+    fn delete_matching_from<F>(root: &mut Option<Self>,
+                               probe: &Interval<T>,
+                               matches: &F) -> Option<(Interval<T>, V)>
+    where
+        F: Fn(&Interval<T>, &V) -> bool
+    {
+        let node = root.as_mut()?;
+        let deleted = node.delete_matching(probe, matches);
+        if node.height < 0 {    // Root has been deleted
+            return root.take().map(|node| (node.interval, node.value));
+        }
+        deleted
+    }
+
+    /// Insert an interval which can not be merged with any interval
+    /// stored in the tree rooted at `root`.
+    fn insert_into(root: &mut Option<Self>, interval: Interval<T>, value: V) {
+        match root {
+            None => { root.replace(Self::with_entry(interval, value)); },
+            Some(node) => { node.insert(interval, value); }
+        }
+    }
+
+    /// Delete an interval from the tree rooted at `root`, passing every
+    /// stored entry overlapping it to `on_overlap`.
+    fn delete_overlapping_from<F>(root: &mut Option<Self>,
+                                  interval: &Interval<T>,
+                                  mut on_overlap: F)
+    where
+        F: FnMut(Interval<T>, &V),
+        V: Clone
+    {
+        // Only the outermost overlapping intervals can stick out of
+        // the deleted interval; their remainders are inserted again
+        // with their original values.
+        let mut left_remainder = None;
+        let mut right_remainder = None;
+        while let Some((other, value)) = Self::delete_matching_from(
+            root, interval, &|other, _| other.overlaps_with(interval)
+        ) {
+            if other.start() < interval.start() {
+                left_remainder = interval.start()
+                    .predecessor()
+                    .map(|stop| (Interval::new(other.start(), stop), value.clone()));
+            }
+            if other.stop() > interval.stop() {
+                right_remainder = interval.stop()
+                    .successor()
+                    .map(|start| (Interval::new(start, other.stop()), value.clone()));
+            }
+            on_overlap(other, &value);
+        }
+        for (remainder, value) in [left_remainder, right_remainder].into_iter().flatten() {
+            Self::insert_into(root, remainder, value);
+        }
+    }
+}
+
+impl<T: Discrete + std::fmt::Display, V> AVLNode<T, V> {
+    fn write_tree<W: std::fmt::Write>(&self,
+                                      writer: &mut W,
+                                      indent: usize) -> std::fmt::Result {
         writeln!(writer, "Node(")?;
         writeln!(writer, "{:indent$}height={}", "", self.height, indent=2*indent + 2)?;
         writeln!(writer, "{:indent$}interval={}", "", self.interval, indent=2*indent + 2)?;
         write!(writer, "{:indent$}left=", "", indent=2*indent + 2)?;
         match &self.left {
             None => writeln!(writer, "null")?,
-            Some(node) => node.print_tree(writer, indent + 1)?
+            Some(node) => node.write_tree(writer, indent + 1)?
         }
         write!(writer, "{:indent$}right=", "", indent=2*indent + 2)?;
         match &self.right {
             None => writeln!(writer, "null")?,
-            Some(node) => node.print_tree(writer, indent + 1)?
+            Some(node) => node.write_tree(writer, indent + 1)?
         }
         writeln!(writer, "{:indent$})", "", indent=2*indent)?;
         Ok(())
     }
+}
 
-    fn is_avl(&self) -> bool {
-        let balance = self.balance_score();
-        (-1..=1).contains(&balance)
+#[derive(Clone, Debug)]
+pub struct AVLIntervalTree<T: Discrete> {
+    root: Option<AVLNode<T>>,
+}
+
+impl<T: Discrete> Default for AVLIntervalTree<T> {
+    fn default() -> Self {
+        Self::empty()
     }
+}
 
-    fn tree_is_avl(&self) -> bool {
-        self.is_avl()
-            && self.left.as_ref().map_or(true, |node| node.tree_is_avl())
-            && self.right.as_ref().map_or(true, |node| node.tree_is_avl())
+impl<T: Discrete> AVLIntervalTree<T> {
+    pub fn is_avl(&self) -> bool {
+        match &self.root {
+            None => true,
+            Some(node) => node.tree_is_avl()
+        }
     }
 
-}
+    /// Iterate over the stored intervals in ascending order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(self.root.as_ref())
+    }
 
-impl<T: num::PrimInt + std::fmt::Display> AVLNode<T> {
-    fn insert(&mut self, new_interval: Interval<T>) {
-        if self.interval.can_merge_with(&new_interval) {
-            self.interval.merge_inplace_unchecked(&new_interval);
-            // merge down recomputes height and re-balances
-            self.merge_down();
-        } else {
-            let child = if new_interval.is_left_of(&self.interval) {
-                &mut self.left
+    /// Iterate over every individual value covered by the tree,
+    /// in ascending order.
+    pub fn values(&self) -> Values<'_, T> {
+        Values::new(self.root.as_ref())
+    }
+
+    /// Return the stored interval containing `value`, if any.
+    pub fn find_containing(&self, value: T) -> Option<Interval<T>> {
+        self.find_containing_interval(&Interval::new(value, value))
+    }
+
+    /// Return the stored interval containing all of `probe`, if any.
+    pub fn find_containing_interval(&self, probe: &Interval<T>) -> Option<Interval<T>> {
+        self.root.as_ref()?.find_containing(probe).map(|node| node.interval)
+    }
+
+    /// Number of values covered by the tree.
+    ///
+    /// Saturates at `u128::MAX` when covering the full domain
+    /// of a 128-bit type.
+    pub fn covered_len(&self) -> u128 {
+        self.root.as_ref().map_or(0, |node| node.covered)
+    }
+
+    /// Number of values within `bounds` covered by the tree.
+    pub fn covered_len_in(&self, bounds: Interval<T>) -> u128 {
+        let Some(node) = &self.root else {
+            return 0;
+        };
+        let mut covered = node.covered_before(bounds.stop());
+        if self.contains_value(bounds.stop()) {
+            covered = covered.saturating_add(1);
+        }
+        covered.saturating_sub(node.covered_before(bounds.start()))
+    }
+
+    /// Number of covered values strictly smaller than `value`.
+    pub fn rank(&self, value: T) -> u128 {
+        self.root.as_ref().map_or(0, |node| node.covered_before(value))
+    }
+
+    /// Return the `k`-th smallest covered value, counting from zero.
+    pub fn select(&self, k: u128) -> Option<T> {
+        self.root.as_ref()?.select(k)
+    }
+
+    /// Return the lowest stored interval.
+    pub fn first(&self) -> Option<Interval<T>> {
+        self.iter().next()
+    }
+
+    /// Return the highest stored interval.
+    pub fn last(&self) -> Option<Interval<T>> {
+        self.iter().next_back()
+    }
+
+    /// Return the lowest stored interval starting after `value`.
+    pub fn next_after(&self, value: T) -> Option<Interval<T>> {
+        let mut node = self.root.as_ref();
+        let mut next = None;
+        while let Some(current) = node {
+            if current.interval.start() > value {
+                next = Some(current.interval);
+                node = current.left.as_deref();
             } else {
-                &mut self.right
-            };
-            match child {
-                None => { child.replace(Self::with_value(new_interval).into()); }
-                Some(node) => { node.insert(new_interval); }
+                node = current.right.as_deref();
             }
-            self.recompute_height();
-            self.balance_after_insertion(new_interval);
         }
+        next
     }
 
-    fn delete(&mut self, interval: &Interval<T>) {
-        println!("Deleting {interval} from {}", self.interval);
-        println!("other contained self: {}", interval.contains_interval(&self.interval));
-        println!("intervals overlap: {}", interval.overlaps_with(&self.interval));
-        println!("self contains other: {}", self.interval.contains_interval(interval));
-        if interval.contains_interval(&self.interval) {
-            // This node must be deleted.
-            // It is actually easier to first delete other child nodes,
-            // and then do deletion as in normal AVL.
-            if let Some(node) = self.right.as_mut() {
-                node.delete(interval);
-            }
-            if let Some(node) = self.left.as_mut() {
-                node.delete(interval);
-            }
-            self.maybe_drop_children();
-            match (self.left.as_mut(), self.right.as_mut()) {
-                (None, None) => {
-                    self.height = 0;    // mark for deletion by parent
-                },
-                (Some(_), None) => {
-                    let node = self.left.take().expect("AVL broken");
-                    *self = *node;
-                },
-                (None, Some(_)) => {
-                    let node = self.right.take().expect("AVL broken");
-                    *self = *node;
-                },
-                (Some(_), Some(_)) => {
-                    self.interval = self.get_and_delete_successor();
-                }
-            }
-        } else if interval.overlaps_with(&self.interval) {
-            if self.interval.contains_interval(interval) {
-                // This node must be split into two nodes
-                let left_interval = Interval::new(
-                    self.interval.start(), interval.start() - T::one()
-                );
-                let right_interval = Interval::new(
-                    interval.stop() + T::one(), self.interval.stop()
-                );
-                self.interval = left_interval;
-                let new_node = Self{
-                    left: None,
-                    right: self.right.take(),
-                    interval: right_interval,
-                    height: self.right_child_height() + 1
-                };
-                self.right = Some(new_node.into());
-                self.right.as_mut().expect("AVL Broken").balance_after_deletion();
+    /// Return the highest stored interval ending before `value`.
+    pub fn prev_before(&self, value: T) -> Option<Interval<T>> {
+        let mut node = self.root.as_ref();
+        let mut prev = None;
+        while let Some(current) = node {
+            if current.interval.stop() < value {
+                prev = Some(current.interval);
+                node = current.right.as_deref();
             } else {
-                // The interval in this node will become smaller.
-                self.interval = if self.interval.contains_value(interval.start()) {
-                    if let Some(node) = self.right.as_mut() {
-                        node.delete(interval);
-                    }
-                    Interval::new(self.interval.start(), interval.start() - T::one())
-                } else {
-                    // assert!(self.interval.contains_value(interval.stop());
-                    if let Some(node) = self.left.as_mut() {
-                        node.delete(interval);
-                    }
-                    Interval::new(interval.stop() + T::one(), self.interval.stop())
-                };
+                node = current.left.as_deref();
             }
-        } else if interval.is_left_of(&self.interval) {
-            if let Some(node) = self.left.as_mut() {
-                node.delete(interval);
-            }
-        } else if let Some(node) = self.right.as_mut() {
-            node.delete(interval);
         }
-        if self.height > 0 {
-            self.maybe_drop_children();
-            self.recompute_height();
-            self.balance_after_deletion();
+        prev
+    }
+
+    /// Return the stored interval closest to `value`.
+    ///
+    /// This is the interval containing `value` if there is one.
+    /// Otherwise, ties between the neighbouring intervals
+    /// are resolved in favour of the lower one.
+    pub fn nearest(&self, value: T) -> Option<Interval<T>> {
+        if let Some(interval) = self.find_containing(value) {
+            return Some(interval);
+        }
+        match (self.prev_before(value), self.next_after(value)) {
+            (Some(prev), Some(next)) => {
+                let prev_distance = Interval::new(prev.stop(), value).size();
+                let next_distance = Interval::new(value, next.start()).size();
+                if prev_distance <= next_distance {
+                    Some(prev)
+                } else {
+                    Some(next)
+                }
+            }
+            (prev, next) => prev.or(next)
         }
     }
 
-    fn contains(&self, interval: &Interval<T>) -> bool {
-        if self.interval.contains_interval(interval) {
-            true
-        } else if interval.is_left_of(&self.interval) {
-            self.left
-                .as_ref()
-                .map_or(false, |node| node.contains(interval))
-        } else {
-            self.right
-                .as_ref()
-                .map_or(false, |node| node.contains(interval))
+    /// Iterate over the stored intervals which overlap `probe`,
+    /// in ascending order.
+    pub fn overlapping(&self, probe: &Interval<T>) -> Range<'_, T> {
+        self.range(*probe)
+    }
+
+    /// Iterate over the intervals which are not covered by the tree,
+    /// in ascending order.
+    pub fn gaps(&self) -> Gaps<'_, T> {
+        self.gaps_in(Interval::unbounded())
+    }
+
+    /// Iterate over the intervals within `bounds` which are not
+    /// covered by the tree, in ascending order.
+    pub fn gaps_in(&self, bounds: Interval<T>) -> Gaps<'_, T> {
+        Gaps::new(self.root.as_ref(), bounds)
+    }
+
+    /// Iterate over the stored intervals which overlap `bounds`,
+    /// in ascending order.
+    pub fn range<R: RangeBounds<T>>(&self, bounds: R) -> Range<'_, T> {
+        Range::new(self.root.as_ref(), bounds)
+    }
+
+    /// Iterate over the stored intervals which overlap `bounds`,
+    /// clipped to `bounds`, in ascending order.
+    pub fn range_clipped<R: RangeBounds<T>>(&self, bounds: R) -> ClippedRange<'_, T> {
+        ClippedRange::new(self.root.as_ref(), bounds)
+    }
+
+    /// Insert an interval, passing every stored interval
+    /// merged into it to `on_absorbed`.
+    fn insert_absorbing<F>(&mut self, interval: Interval<T>, mut on_absorbed: F)
+    where
+        F: FnMut(Interval<T>)
+    {
+        // Absorb all stored intervals which overlap or touch the new one,
+        // so that the stored intervals stay disjoint and non-adjacent.
+        let mut interval = interval;
+        loop {
+            let probe = interval;
+            match AVLNode::delete_matching_from(
+                &mut self.root, &probe, &|other, _| other.can_merge_with(&probe)
+            ) {
+                None => break,
+                Some((other, ())) => {
+                    interval.merge_inplace_unchecked(&other);
+                    on_absorbed(other);
+                }
+            }
         }
+        AVLNode::insert_into(&mut self.root, interval, ());
     }
 
-    fn tree_size(&self) -> i32 {
-        let left_size = self.left
-            .as_ref()
-            .map_or(0, |n| n.tree_size());
-        let right_size = self.right
-            .as_ref()
-            .map_or(0, |n| n.tree_size());
-        left_size + right_size + 1
+    fn from_sorted(intervals: &[Interval<T>]) -> Self {
+        Self{root: AVLNode::from_sorted(intervals).map(|node| *node)}
     }
 }
 
-pub struct AVLIntervalTree<T: num::PrimInt + std::fmt::Display> {
-    root: Option<AVLNode<T>>,
-}
+impl<T: Discrete + std::fmt::Display> AVLIntervalTree<T> {
+    /// Print the structure of the tree to stdout.
+    pub fn print_tree(&self) -> Result<(), IntervalError> {
+        self.write_tree(&mut std::io::stdout().lock())
+    }
 
-impl<T: num::PrimInt + std::fmt::Display> AVLIntervalTree<T> {
-    pub fn print_tree(&self) -> Result<(), Box<dyn Error>> {
-        let mut writer = std::io::Cursor::new(Vec::<u8>::new());
+    /// Write the structure of the tree to `writer`.
+    pub fn write_tree<W: std::io::Write>(&self, writer: &mut W) -> Result<(), IntervalError> {
+        let mut adapter = IoWriter{inner: writer, error: None};
+        let result = self.write_tree_fmt(&mut adapter);
+        match adapter.error {
+            Some(error) => Err(error.into()),
+            None => result
+        }
+    }
+
+    /// Write the structure of the tree to a formatter or string.
+    pub fn write_tree_fmt<W: std::fmt::Write>(&self, writer: &mut W) -> Result<(), IntervalError> {
         match &self.root {
             None => writeln!(writer, "Tree()")?,
             Some(node) => {
                 writeln!(writer, "Tree(")?;
                 write!(writer, "  ")?;
-                node.print_tree(&mut writer, 1)?;
+                node.write_tree(writer, 1)?;
                 writeln!(writer, ")")?;
             }
         }
-        writer.set_position(0);
-        let buffer = writer.into_inner();
-        let text = std::str::from_utf8(&buffer)?;
-        println!("{}", text);
         Ok(())
     }
+}
 
-    pub fn is_avl(&self) -> bool {
-        match &self.root {
-            None => true,
-            Some(node) => node.tree_is_avl()
-        }
+/// Streams formatted output into an `io::Write`, keeping the
+/// I/O error which `fmt::Error` can not carry.
+struct IoWriter<'a, W: std::io::Write> {
+    inner: &'a mut W,
+    error: Option<std::io::Error>
+}
+
+impl<W: std::io::Write> std::fmt::Write for IoWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            std::fmt::Error
+        })
     }
 }
 
-impl<T: num::PrimInt + std::fmt::Display> IntervalTree<T> for AVLIntervalTree<T> {
+impl<T: Discrete> IntervalTree<T> for AVLIntervalTree<T> {
     fn empty() -> Self {
         Self{root: None}
     }
@@ -429,29 +715,89 @@ impl<T: num::PrimInt + std::fmt::Display> IntervalTree<T> for AVLIntervalTree<T>
         }
     }
 
-    fn insert(&mut self, interval: Interval<T>) {
-        match &mut self.root {
-            None => { self.root.replace(AVLNode::with_value(interval)); },
-            Some(node) => { node.insert(interval); }
-        }
+    fn insert<I: Into<Interval<T>>>(&mut self, interval: I) {
+        self.insert_absorbing(interval.into(), |_| {});
     }
 
-    fn delete(&mut self, interval: &Interval<T>) {
-        match &mut self.root {
-            None => {}
-            Some(node) => {
-                node.delete(interval);
-                if node.height < 0 {    // Root has been deleted 
-                    self.root.take();
-                }
-            }
-        }
+    fn insert_reporting<I: Into<Interval<T>>>(&mut self, interval: I) -> Vec<Interval<T>> {
+        let interval = interval.into();
+        let mut absorbed = Vec::new();
+        self.insert_absorbing(interval, |other| absorbed.push(other));
+        absorbed.sort_by_key(|other| other.start());
+        set_ops::difference(std::iter::once(interval), absorbed)
+    }
+
+    fn delete<I: Into<Interval<T>>>(&mut self, interval: I) {
+        AVLNode::delete_overlapping_from(&mut self.root, &interval.into(), |_, _| {});
+    }
+
+    fn delete_reporting<I: Into<Interval<T>>>(&mut self, interval: I) -> Vec<Interval<T>> {
+        let interval = interval.into();
+        let mut removed = Vec::new();
+        AVLNode::delete_overlapping_from(&mut self.root, &interval, |other, _| removed.push(
+            Interval::new(other.start().max(interval.start()), other.stop().min(interval.stop()))
+        ));
+        removed.sort_by_key(|other| other.start());
+        removed
     }
 
     fn contains(&self, interval: &Interval<T>) -> bool {
         match self.root {
             None => false,
-            Some(ref node) => node.contains(interval)
+            Some(ref node) => node.find_containing(interval).is_some()
+        }
+    }
+}
+
+impl<'a, T: Discrete> IntoIterator for &'a AVLIntervalTree<T> {
+    type Item = Interval<T>;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Discrete> IntoIterator for AVLIntervalTree<T> {
+    type Item = Interval<T>;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self.root)
+    }
+}
+
+/// Renders the covered values as a set of intervals, e.g. `{[1, 3], [7, 9]}`.
+impl<T: Discrete + std::fmt::Display> std::fmt::Display for AVLIntervalTree<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{{")?;
+        for (i, interval) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{interval}")?;
+        }
+        write!(f, "}}")
+    }
+}
+
+/// Trees are equal when they cover the same values,
+/// regardless of their internal shape.
+impl<T: Discrete> PartialEq for AVLIntervalTree<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Discrete> Eq for AVLIntervalTree<T> {}
+
+impl<T: Discrete + Hash> Hash for AVLIntervalTree<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut count = 0usize;
+        for interval in self {
+            interval.hash(state);
+            count += 1;
         }
+        count.hash(state);
     }
 }
@@ -0,0 +1,353 @@
+use std::net::Ipv4Addr;
+use std::ops::RangeBounds;
+use crate::discrete::Discrete;
+use crate::interval::Interval;
+use super::AVLNode;
+
+/// Iterator over the intervals stored in an `AVLIntervalTree`,
+/// in ascending order.
+pub struct Iter<'a, T: Discrete> {
+    inner: Range<'a, T>
+}
+
+impl<'a, T: Discrete> Iter<'a, T> {
+    pub(super) fn new(root: Option<&'a AVLNode<T>>) -> Self {
+        Self{inner: Range::new(root, ..)}
+    }
+}
+
+impl<T: Discrete> Iterator for Iter<'_, T> {
+    type Item = Interval<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+impl<T: Discrete> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+/// Iterator over the intervals stored in an `AVLIntervalTree` which
+/// overlap a window, in ascending order.
+///
+/// Intervals sticking out of the window are yielded unchanged.
+pub struct Range<'a, T: Discrete> {
+    nodes: Nodes<'a, T, ()>
+}
+
+impl<'a, T: Discrete> Range<'a, T> {
+    pub(super) fn new<R: RangeBounds<T>>(root: Option<&'a AVLNode<T>>, bounds: R) -> Self {
+        Self{nodes: Nodes::new(root, bounds)}
+    }
+}
+
+impl<T: Discrete> Iterator for Range<'_, T> {
+    type Item = Interval<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(|node| node.interval)
+    }
+}
+
+impl<T: Discrete> DoubleEndedIterator for Range<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.nodes.next_back().map(|node| node.interval)
+    }
+}
+
+/// Iterator over the nodes of a tree whose intervals overlap a window,
+/// in ascending order.
+struct Nodes<'a, T: Discrete, V> {
+    window: Option<Interval<T>>,
+    front: Vec<&'a AVLNode<T, V>>,
+    back: Vec<&'a AVLNode<T, V>>,
+    last_front: Option<Interval<T>>,
+    last_back: Option<Interval<T>>
+}
+
+impl<'a, T: Discrete, V> Nodes<'a, T, V> {
+    fn new<R: RangeBounds<T>>(root: Option<&'a AVLNode<T, V>>, bounds: R) -> Self {
+        let window = Interval::from_bounds(
+            bounds.start_bound().cloned(), bounds.end_bound().cloned()
+        ).ok();
+        let mut range = Self{
+            window,
+            front: Vec::new(),
+            back: Vec::new(),
+            last_front: None,
+            last_back: None
+        };
+        if let Some(window) = window {
+            range.seek_front(root, window.start());
+            range.seek_back(root, window.stop());
+        }
+        range
+    }
+
+    /// Push the path towards the first interval ending at or after `start`.
+    fn seek_front(&mut self, mut node: Option<&'a AVLNode<T, V>>, start: T) {
+        while let Some(current) = node {
+            if current.interval.stop() < start {
+                node = current.right.as_deref();
+            } else {
+                self.front.push(current);
+                node = current.left.as_deref();
+            }
+        }
+    }
+
+    /// Push the path towards the last interval starting at or before `stop`.
+    fn seek_back(&mut self, mut node: Option<&'a AVLNode<T, V>>, stop: T) {
+        while let Some(current) = node {
+            if current.interval.start() > stop {
+                node = current.left.as_deref();
+            } else {
+                self.back.push(current);
+                node = current.right.as_deref();
+            }
+        }
+    }
+
+    fn push_left_spine(&mut self, mut node: Option<&'a AVLNode<T, V>>) {
+        while let Some(current) = node {
+            self.front.push(current);
+            node = current.left.as_deref();
+        }
+    }
+
+    fn push_right_spine(&mut self, mut node: Option<&'a AVLNode<T, V>>) {
+        while let Some(current) = node {
+            self.back.push(current);
+            node = current.right.as_deref();
+        }
+    }
+
+    fn finish(&mut self) -> Option<&'a AVLNode<T, V>> {
+        self.front.clear();
+        self.back.clear();
+        None
+    }
+}
+
+impl<'a, T: Discrete, V> Iterator for Nodes<'a, T, V> {
+    type Item = &'a AVLNode<T, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.front.pop()?;
+        let interval = node.interval;
+        let window = self.window.expect("non-empty range without window");
+        let passed_back = self.last_back
+            .is_some_and(|last| last.start() <= interval.start());
+        if interval.is_right_of(&window) || passed_back {
+            return self.finish();
+        }
+        self.push_left_spine(node.right.as_deref());
+        self.last_front = Some(interval);
+        Some(node)
+    }
+}
+
+impl<T: Discrete, V> DoubleEndedIterator for Nodes<'_, T, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.back.pop()?;
+        let interval = node.interval;
+        let window = self.window.expect("non-empty range without window");
+        let passed_front = self.last_front
+            .is_some_and(|last| last.start() >= interval.start());
+        if interval.is_left_of(&window) || passed_front {
+            return self.finish();
+        }
+        self.push_right_spine(node.left.as_deref());
+        self.last_back = Some(interval);
+        Some(node)
+    }
+}
+
+/// Iterator over the intervals stored in an `AVLIntervalTree` which
+/// overlap a window, clipped to that window, in ascending order.
+pub struct ClippedRange<'a, T: Discrete> {
+    inner: Range<'a, T>
+}
+
+impl<'a, T: Discrete> ClippedRange<'a, T> {
+    pub(super) fn new<R: RangeBounds<T>>(root: Option<&'a AVLNode<T>>, bounds: R) -> Self {
+        Self{inner: Range::new(root, bounds)}
+    }
+
+    fn clip(&self, interval: Interval<T>) -> Interval<T> {
+        let window = self.inner.nodes.window.expect("non-empty range without window");
+        Interval::new(
+            interval.start().max(window.start()),
+            interval.stop().min(window.stop())
+        )
+    }
+}
+
+impl<T: Discrete> Iterator for ClippedRange<'_, T> {
+    type Item = Interval<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|interval| self.clip(interval))
+    }
+}
+
+impl<T: Discrete> DoubleEndedIterator for ClippedRange<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|interval| self.clip(interval))
+    }
+}
+
+/// Iterator over the intervals within a window which are not
+/// covered by an `AVLIntervalTree`, in ascending order.
+pub struct Gaps<'a, T: Discrete> {
+    intervals: Range<'a, T>,
+    next_start: Option<T>,
+    stop: T
+}
+
+impl<'a, T: Discrete> Gaps<'a, T> {
+    pub(super) fn new(root: Option<&'a AVLNode<T>>, bounds: Interval<T>) -> Self {
+        Self{
+            intervals: Range::new(root, bounds.start()..=bounds.stop()),
+            next_start: Some(bounds.start()),
+            stop: bounds.stop()
+        }
+    }
+}
+
+impl<T: Discrete> Iterator for Gaps<'_, T> {
+    type Item = Interval<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let start = self.next_start?;
+            let Some(interval) = self.intervals.next() else {
+                self.next_start = None;
+                return Some(Interval::new(start, self.stop));
+            };
+            // Never step past `stop`, which may be `T::MAX`.
+            self.next_start = interval.stop()
+                .successor()
+                .filter(|next| *next <= self.stop);
+            if interval.start() > start {
+                if let Some(stop) = interval.start().predecessor() {
+                    return Some(Interval::new(start, stop));
+                }
+            }
+        }
+    }
+}
+
+/// Iterator over the individual values covered by an `AVLIntervalTree`,
+/// in ascending order.
+pub struct Values<'a, T: Discrete> {
+    intervals: Iter<'a, T>,
+    current: Option<(T, T)>,
+    remaining: u128
+}
+
+impl<'a, T: Discrete> Values<'a, T> {
+    pub(super) fn new(root: Option<&'a AVLNode<T>>) -> Self {
+        let remaining = root.map_or(0, |node| node.covered);
+        Self{intervals: Iter::new(root), current: None, remaining}
+    }
+}
+
+impl<T: Discrete> Iterator for Values<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (value, stop) = match self.current {
+            Some(current) => current,
+            None => {
+                let interval = self.intervals.next()?;
+                (interval.start(), interval.stop())
+            }
+        };
+        // Never step past `stop`, which may be `T::MAX`.
+        self.current = value.successor()
+            .filter(|next| *next <= stop)
+            .map(|next| (next, stop));
+        self.remaining = self.remaining.saturating_sub(1);
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match usize::try_from(self.remaining) {
+            Ok(remaining) => (remaining, Some(remaining)),
+            Err(_) => (usize::MAX, None)
+        }
+    }
+}
+
+macro_rules! impl_exact_size_values {
+    ($($t:ty),*) => {
+        $(impl ExactSizeIterator for Values<'_, $t> {})*
+    };
+}
+
+impl_exact_size_values!(i8, u8, i16, u16);
+#[cfg(any(target_pointer_width = "32", target_pointer_width = "64"))]
+impl_exact_size_values!(char);
+#[cfg(target_pointer_width = "64")]
+impl_exact_size_values!(i32, u32, Ipv4Addr);
+
+/// Owning iterator over the intervals stored in an `AVLIntervalTree`,
+/// in ascending order.
+pub struct IntoIter<T: Discrete> {
+    stack: Vec<AVLNode<T>>
+}
+
+impl<T: Discrete> IntoIter<T> {
+    pub(super) fn new(root: Option<AVLNode<T>>) -> Self {
+        let mut iter = Self{stack: Vec::new()};
+        iter.push_left_spine(root);
+        iter
+    }
+
+    fn push_left_spine(&mut self, mut node: Option<AVLNode<T>>) {
+        while let Some(mut current) = node {
+            node = current.left.take().map(|child| *child);
+            self.stack.push(current);
+        }
+    }
+}
+
+impl<T: Discrete> Iterator for IntoIter<T> {
+    type Item = Interval<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut node = self.stack.pop()?;
+        self.push_left_spine(node.right.take().map(|child| *child));
+        Some(node.interval)
+    }
+}
+
+/// Iterator over the entries stored in an `AVLIntervalMap`,
+/// in ascending order.
+pub struct MapIter<'a, K: Discrete, V> {
+    nodes: Nodes<'a, K, V>
+}
+
+impl<'a, K: Discrete, V> MapIter<'a, K, V> {
+    pub(super) fn new(root: Option<&'a AVLNode<K, V>>) -> Self {
+        Self{nodes: Nodes::new(root, ..)}
+    }
+}
+
+impl<'a, K: Discrete, V> Iterator for MapIter<'a, K, V> {
+    type Item = (Interval<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(|node| (node.interval, &node.value))
+    }
+}
+
+impl<K: Discrete, V> DoubleEndedIterator for MapIter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.nodes.next_back().map(|node| (node.interval, &node.value))
+    }
+}
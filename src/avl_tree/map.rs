@@ -0,0 +1,110 @@
+use crate::discrete::Discrete;
+use crate::interval::Interval;
+use super::{AVLNode, MapIter};
+
+/// A map from disjoint intervals of keys to values, stored in an AVL tree.
+///
+/// Adjacent intervals are only merged if their values are equal, so
+/// `[0, 4] => a` and `[5, 9] => a` are stored as `[0, 9] => a`, while
+/// `[0, 4] => a` and `[5, 9] => b` stay separate.
+#[derive(Clone, Debug)]
+pub struct AVLIntervalMap<K: Discrete, V> {
+    root: Option<AVLNode<K, V>>
+}
+
+impl<K: Discrete, V: Clone + PartialEq> Default for AVLIntervalMap<K, V> {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<K: Discrete, V: Clone + PartialEq> AVLIntervalMap<K, V> {
+    /// Create and return a new empty map.
+    pub fn empty() -> Self {
+        Self{root: None}
+    }
+
+    /// Check whether the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Amount of nodes in the map.
+    pub fn number_of_nodes(&self) -> i32 {
+        self.root.as_ref().map_or(0, |node| node.tree_size())
+    }
+
+    pub fn is_avl(&self) -> bool {
+        self.root.as_ref().is_none_or(|node| node.tree_is_avl())
+    }
+
+    /// Map every key in `interval` to `value`, overwriting
+    /// the values previously stored for these keys.
+    pub fn insert<I: Into<Interval<K>>>(&mut self, interval: I, value: V) {
+        let mut interval = interval.into();
+        self.remove(interval);
+        // Absorb the neighbouring intervals if they map to the same value,
+        // so that adjacent intervals always map to different values.
+        loop {
+            let probe = interval;
+            match AVLNode::delete_matching_from(
+                &mut self.root,
+                &probe,
+                &|other, other_value| other.adjacent_to(&probe) && *other_value == value
+            ) {
+                None => break,
+                Some((other, _)) => interval.merge_inplace_unchecked(&other)
+            }
+        }
+        AVLNode::insert_into(&mut self.root, interval, value);
+    }
+
+    /// Remove every key in `interval` from the map.
+    ///
+    /// Stored intervals sticking out of `interval` keep their value
+    /// for the remaining keys.
+    pub fn remove<I: Into<Interval<K>>>(&mut self, interval: I) {
+        AVLNode::delete_overlapping_from(&mut self.root, &interval.into(), |_, _| {});
+    }
+
+    /// Return the value mapped to `key`, if any.
+    pub fn get(&self, key: K) -> Option<&V> {
+        self.get_key_value(key).map(|(_, value)| value)
+    }
+
+    /// Return the stored interval containing `key` and its value, if any.
+    pub fn get_key_value(&self, key: K) -> Option<(Interval<K>, &V)> {
+        let node = self.root.as_ref()?.find_containing(&Interval::new(key, key))?;
+        Some((node.interval, &node.value))
+    }
+
+    /// Check whether `key` is mapped to a value.
+    pub fn contains_key(&self, key: K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Iterate over the stored intervals and their values,
+    /// in ascending order.
+    pub fn iter(&self) -> MapIter<'_, K, V> {
+        MapIter::new(self.root.as_ref())
+    }
+}
+
+impl<'a, K: Discrete, V> IntoIterator for &'a AVLIntervalMap<K, V> {
+    type Item = (Interval<K>, &'a V);
+    type IntoIter = MapIter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        MapIter::new(self.root.as_ref())
+    }
+}
+
+/// Maps are equal when they map the same keys to equal values,
+/// regardless of their internal shape.
+impl<K: Discrete, V: PartialEq> PartialEq for AVLIntervalMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.into_iter().eq(other)
+    }
+}
+
+impl<K: Discrete, V: Eq> Eq for AVLIntervalMap<K, V> {}
@@ -0,0 +1,261 @@
+use std::iter::Peekable;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Sub, SubAssign};
+use crate::discrete::Discrete;
+use crate::interval::Interval;
+use super::AVLIntervalTree;
+
+/// Append an interval to a sorted list of intervals,
+/// merging it with the last interval if possible.
+fn push_coalesced<T>(intervals: &mut Vec<Interval<T>>, interval: Interval<T>)
+where
+    T: Discrete
+{
+    match intervals.last_mut() {
+        Some(last) if last.can_merge_with(&interval) => {
+            last.merge_inplace_unchecked(&interval);
+        }
+        _ => intervals.push(interval)
+    }
+}
+
+/// Pop the interval with the smallest start from either of two sorted sequences.
+fn next_by_start<T, I, J>(left: &mut Peekable<I>, right: &mut Peekable<J>) -> Option<Interval<T>>
+where
+    T: Discrete,
+    I: Iterator<Item=Interval<T>>,
+    J: Iterator<Item=Interval<T>>
+{
+    match (left.peek(), right.peek()) {
+        (Some(a), Some(b)) if b.start() < a.start() => right.next(),
+        (Some(_), _) => left.next(),
+        (None, _) => right.next()
+    }
+}
+
+/// Union of two sorted sequences of intervals.
+fn union<T, I, J>(left: I, right: J) -> Vec<Interval<T>>
+where
+    T: Discrete,
+    I: IntoIterator<Item=Interval<T>>,
+    J: IntoIterator<Item=Interval<T>>
+{
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+    let mut result = Vec::new();
+    while let Some(interval) = next_by_start(&mut left, &mut right) {
+        push_coalesced(&mut result, interval);
+    }
+    result
+}
+
+/// Intersection of two sorted sequences of intervals.
+fn intersection<T, I, J>(left: I, right: J) -> Vec<Interval<T>>
+where
+    T: Discrete,
+    I: IntoIterator<Item=Interval<T>>,
+    J: IntoIterator<Item=Interval<T>>
+{
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+    let mut result = Vec::new();
+    while let (Some(&a), Some(&b)) = (left.peek(), right.peek()) {
+        if a.overlaps_with(&b) {
+            push_coalesced(&mut result, Interval::new(
+                a.start().max(b.start()), a.stop().min(b.stop())
+            ));
+        }
+        // The interval ending first can not overlap anything else.
+        if a.stop() < b.stop() {
+            left.next();
+        } else {
+            right.next();
+        }
+    }
+    result
+}
+
+/// Difference of two sorted sequences of intervals.
+pub(super) fn difference<T, I, J>(left: I, right: J) -> Vec<Interval<T>>
+where
+    T: Discrete,
+    I: IntoIterator<Item=Interval<T>>,
+    J: IntoIterator<Item=Interval<T>>
+{
+    let mut right = right.into_iter().peekable();
+    let mut result = Vec::new();
+    for interval in left {
+        // The part of `interval` not yet compared against `right`
+        let mut remainder = Some(interval);
+        while let (Some(current), Some(&other)) = (remainder, right.peek()) {
+            if other.is_left_of(&current) {
+                right.next();
+                continue;
+            }
+            if other.is_right_of(&current) {
+                break;
+            }
+            if other.start() > current.start() {
+                if let Some(stop) = other.start().predecessor() {
+                    push_coalesced(&mut result, Interval::new(current.start(), stop));
+                }
+            }
+            if other.stop() < current.stop() {
+                remainder = other.stop()
+                    .successor()
+                    .map(|start| Interval::new(start, current.stop()));
+                right.next();
+            } else {
+                // `other` may still overlap the next interval of `left`.
+                remainder = None;
+            }
+        }
+        if let Some(current) = remainder {
+            push_coalesced(&mut result, current);
+        }
+    }
+    result
+}
+
+impl<T: Discrete> AVLIntervalTree<T> {
+    /// Return a tree covering all values covered by either tree.
+    pub fn union(&self, other: &Self) -> Self {
+        Self::from_sorted(&union(self, other))
+    }
+
+    /// Add all values covered by `other` to this tree.
+    pub fn union_with(&mut self, other: &Self) {
+        *self = self.union(other);
+    }
+
+    /// Return a tree covering all values covered by both trees.
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self::from_sorted(&intersection(self, other))
+    }
+
+    /// Remove all values not covered by `other` from this tree.
+    pub fn intersect_with(&mut self, other: &Self) {
+        *self = self.intersection(other);
+    }
+
+    /// Return a tree covering all values covered by this tree but not by `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        Self::from_sorted(&difference(self, other))
+    }
+
+    /// Remove all values covered by `other` from this tree.
+    pub fn difference_with(&mut self, other: &Self) {
+        *self = self.difference(other);
+    }
+
+    /// Return a tree covering all values covered by exactly one of both trees.
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        Self::from_sorted(&union(difference(self, other), difference(other, self)))
+    }
+
+    /// Replace this tree by the values covered by exactly one of both trees.
+    pub fn symmetric_difference_with(&mut self, other: &Self) {
+        *self = self.symmetric_difference(other);
+    }
+
+    /// Check whether all values covered by this tree are covered by `other`.
+    pub fn is_subset(&self, other: &Self) -> bool {
+        let mut others = other.iter().peekable();
+        self.iter().all(|interval| {
+            while others.next_if(|other| other.is_left_of(&interval)).is_some() {}
+            others.peek().is_some_and(|other| other.contains_interval(&interval))
+        })
+    }
+
+    /// Check whether all values covered by `other` are covered by this tree.
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Check whether no value is covered by both trees.
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        !self.overlaps(other)
+    }
+
+    /// Check whether some value is covered by both trees.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        let mut left = self.iter().peekable();
+        let mut right = other.iter().peekable();
+        while let (Some(&a), Some(&b)) = (left.peek(), right.peek()) {
+            if a.overlaps_with(&b) {
+                return true;
+            }
+            if a.stop() < b.stop() {
+                left.next();
+            } else {
+                right.next();
+            }
+        }
+        false
+    }
+
+    /// Return a tree covering all values in `bounds` which are not covered by this tree.
+    pub fn complement_within(&self, bounds: Interval<T>) -> Self {
+        Self::from_sorted(&self.gaps_in(bounds).collect::<Vec<_>>())
+    }
+
+    /// Return a tree covering all values which are not covered by this tree.
+    pub fn complement(&self) -> Self {
+        self.complement_within(Interval::unbounded())
+    }
+}
+
+impl<T: Discrete> BitOr<&AVLIntervalTree<T>> for &AVLIntervalTree<T> {
+    type Output = AVLIntervalTree<T>;
+
+    fn bitor(self, rhs: &AVLIntervalTree<T>) -> Self::Output {
+        self.union(rhs)
+    }
+}
+
+impl<T: Discrete> BitOrAssign<&AVLIntervalTree<T>> for AVLIntervalTree<T> {
+    fn bitor_assign(&mut self, rhs: &AVLIntervalTree<T>) {
+        self.union_with(rhs);
+    }
+}
+
+impl<T: Discrete> BitAnd<&AVLIntervalTree<T>> for &AVLIntervalTree<T> {
+    type Output = AVLIntervalTree<T>;
+
+    fn bitand(self, rhs: &AVLIntervalTree<T>) -> Self::Output {
+        self.intersection(rhs)
+    }
+}
+
+impl<T: Discrete> BitAndAssign<&AVLIntervalTree<T>> for AVLIntervalTree<T> {
+    fn bitand_assign(&mut self, rhs: &AVLIntervalTree<T>) {
+        self.intersect_with(rhs);
+    }
+}
+
+impl<T: Discrete> Sub<&AVLIntervalTree<T>> for &AVLIntervalTree<T> {
+    type Output = AVLIntervalTree<T>;
+
+    fn sub(self, rhs: &AVLIntervalTree<T>) -> Self::Output {
+        self.difference(rhs)
+    }
+}
+
+impl<T: Discrete> SubAssign<&AVLIntervalTree<T>> for AVLIntervalTree<T> {
+    fn sub_assign(&mut self, rhs: &AVLIntervalTree<T>) {
+        self.difference_with(rhs);
+    }
+}
+
+impl<T: Discrete> BitXor<&AVLIntervalTree<T>> for &AVLIntervalTree<T> {
+    type Output = AVLIntervalTree<T>;
+
+    fn bitxor(self, rhs: &AVLIntervalTree<T>) -> Self::Output {
+        self.symmetric_difference(rhs)
+    }
+}
+
+impl<T: Discrete> BitXorAssign<&AVLIntervalTree<T>> for AVLIntervalTree<T> {
+    fn bitxor_assign(&mut self, rhs: &AVLIntervalTree<T>) {
+        self.symmetric_difference_with(rhs);
+    }
+}
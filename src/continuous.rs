@@ -0,0 +1,317 @@
+use std::collections::{btree_map, BTreeMap};
+use std::fmt::Formatter;
+use std::ops::{Bound, Range, RangeBounds};
+use crate::interval::IntervalError;
+
+
+/// A non-empty half-open interval `[start, end)` over a continuous domain.
+///
+/// Unlike `Interval`, no notion of adjacent values is needed, so any
+/// totally ordered type can be used, e.g. `f64` seconds wrapped in a
+/// total-order type.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ContinuousInterval<T: Ord + Copy> {
+    start: T,
+    end: T
+}
+
+impl<T: Ord + Copy + std::fmt::Display> std::fmt::Display for ContinuousInterval<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}, {})", self.start, self.end)
+    }
+}
+
+impl<T: Ord + Copy> ContinuousInterval<T> {
+    /// Create the interval `[start, end)`.
+    ///
+    /// `start` must be smaller than `end`;
+    /// this is only checked in debug builds.
+    pub fn new(start: T, end: T) -> Self {
+        debug_assert!(start < end, "interval start is not smaller than its end");
+        Self{start, end}
+    }
+
+    /// Create the interval `[start, end)`, or fail if it contains no values.
+    pub fn try_new(start: T, end: T) -> Result<Self, IntervalError> {
+        if start < end {
+            Ok(Self{start, end})
+        } else if start == end {
+            Err(IntervalError::Empty)
+        } else {
+            Err(IntervalError::Inverted)
+        }
+    }
+
+    pub fn start(&self) -> T {
+        self.start
+    }
+
+    pub fn end(&self) -> T {
+        self.end
+    }
+
+    pub fn contains_value(&self, value: T) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    pub fn contains_interval(&self, other: &ContinuousInterval<T>) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    pub fn overlaps_with(&self, other: &ContinuousInterval<T>) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+
+    /// Check whether the intervals share an endpoint without overlapping.
+    pub fn touches(&self, other: &ContinuousInterval<T>) -> bool {
+        self.end == other.start || other.end == self.start
+    }
+
+    pub fn can_merge_with(&self, other: &ContinuousInterval<T>) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    pub fn merge(&self, other: &ContinuousInterval<T>) -> Result<ContinuousInterval<T>, IntervalError> {
+        if self.can_merge_with(other) {
+            Ok(self.merge_unchecked(other))
+        } else {
+            Err(IntervalError::MergeOnDisjointIntervals)
+        }
+    }
+
+    pub fn merge_unchecked(&self, other: &ContinuousInterval<T>) -> ContinuousInterval<T> {
+        Self{
+            start: self.start.min(other.start),
+            end: self.end.max(other.end)
+        }
+    }
+
+    pub fn is_left_of(&self, other: &ContinuousInterval<T>) -> bool {
+        self.end <= other.start
+    }
+
+    pub fn is_right_of(&self, other: &ContinuousInterval<T>) -> bool {
+        self.start >= other.end
+    }
+}
+
+/// Panics if the range is empty.
+impl<T: Ord + Copy> From<Range<T>> for ContinuousInterval<T> {
+    fn from(range: Range<T>) -> Self {
+        assert!(range.start < range.end, "cannot create an interval from an empty range");
+        Self{start: range.start, end: range.end}
+    }
+}
+
+impl<T: Ord + Copy> From<&ContinuousInterval<T>> for ContinuousInterval<T> {
+    fn from(interval: &ContinuousInterval<T>) -> Self {
+        *interval
+    }
+}
+
+impl<T: Ord + Copy> From<ContinuousInterval<T>> for Range<T> {
+    fn from(interval: ContinuousInterval<T>) -> Self {
+        interval.start..interval.end
+    }
+}
+
+impl<T: Ord + Copy> RangeBounds<T> for ContinuousInterval<T> {
+    fn start_bound(&self) -> Bound<&T> {
+        Bound::Included(&self.start)
+    }
+
+    fn end_bound(&self) -> Bound<&T> {
+        Bound::Excluded(&self.end)
+    }
+}
+
+
+/// A set of values from a continuous domain, stored as disjoint
+/// half-open intervals.
+///
+/// Intervals are merged when they overlap or share an endpoint,
+/// so `[0, 1)` and `[1, 2)` are stored as `[0, 2)`.
+///
+/// This is a separate, smaller structure than `AVLIntervalTree`, kept in
+/// a `BTreeMap` from interval starts to ends. Without a notion of adjacent
+/// values there is no count of covered values, so the operations built
+/// on it (`covered_len`, `values`, `rank`/`select`) and gaps computed via
+/// successors do not carry over; only insertion, deletion, lookup and
+/// iteration are provided.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ContinuousIntervalSet<T: Ord + Copy> {
+    /// Maps the start of every stored interval to its end.
+    intervals: BTreeMap<T, T>
+}
+
+impl<T: Ord + Copy> Default for ContinuousIntervalSet<T> {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T: Ord + Copy> ContinuousIntervalSet<T> {
+    /// Create and return a new empty set.
+    pub fn empty() -> Self {
+        Self{intervals: BTreeMap::new()}
+    }
+
+    /// Check whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Amount of disjoint intervals stored in the set.
+    pub fn number_of_intervals(&self) -> usize {
+        self.intervals.len()
+    }
+
+    /// Insert an interval into the set.
+    pub fn insert<I: Into<ContinuousInterval<T>>>(&mut self, interval: I) {
+        let mut interval = interval.into();
+        if let Some(before) = self.last_starting_at_or_before(interval.start) {
+            if before.can_merge_with(&interval) {
+                interval = interval.merge_unchecked(&before);
+            }
+        }
+        // All other mergeable intervals start within the new interval.
+        let absorbed: Vec<ContinuousInterval<T>> = self.intervals
+            .range(interval.start..=interval.end)
+            .map(|(&start, &end)| ContinuousInterval{start, end})
+            .collect();
+        for other in absorbed {
+            self.intervals.remove(&other.start);
+            interval = interval.merge_unchecked(&other);
+        }
+        self.intervals.insert(interval.start, interval.end);
+    }
+
+    /// Delete an interval from the set.
+    pub fn delete<I: Into<ContinuousInterval<T>>>(&mut self, interval: I) {
+        let interval = interval.into();
+        if let Some(before) = self.last_starting_at_or_before(interval.start) {
+            if before.start < interval.start && before.overlaps_with(&interval) {
+                self.intervals.insert(before.start, interval.start);
+                if before.end > interval.end {
+                    self.intervals.insert(interval.end, before.end);
+                }
+            }
+        }
+        let overlapping: Vec<ContinuousInterval<T>> = self.intervals
+            .range(interval.start..interval.end)
+            .map(|(&start, &end)| ContinuousInterval{start, end})
+            .collect();
+        for other in overlapping {
+            self.intervals.remove(&other.start);
+            if other.end > interval.end {
+                self.intervals.insert(interval.end, other.end);
+            }
+        }
+    }
+
+    /// Check whether an interval is contained in the set.
+    pub fn contains(&self, interval: &ContinuousInterval<T>) -> bool {
+        self.find_containing_interval(interval).is_some()
+    }
+
+    /// Check whether a single value is contained in the set.
+    pub fn contains_value(&self, value: T) -> bool {
+        self.find_containing(value).is_some()
+    }
+
+    /// Return the stored interval containing `value`, if any.
+    pub fn find_containing(&self, value: T) -> Option<ContinuousInterval<T>> {
+        self.last_starting_at_or_before(value)
+            .filter(|interval| interval.contains_value(value))
+    }
+
+    /// Return the stored interval containing all of `probe`, if any.
+    pub fn find_containing_interval(&self, probe: &ContinuousInterval<T>) -> Option<ContinuousInterval<T>> {
+        self.last_starting_at_or_before(probe.start)
+            .filter(|interval| interval.contains_interval(probe))
+    }
+
+    /// Iterate over the stored intervals in ascending order.
+    pub fn iter(&self) -> ContinuousIter<'_, T> {
+        ContinuousIter{inner: self.intervals.iter()}
+    }
+
+    /// Iterate over the stored intervals which overlap `probe`,
+    /// in ascending order.
+    pub fn overlapping(&self, probe: &ContinuousInterval<T>) -> ContinuousOverlapping<'_, T> {
+        ContinuousOverlapping{
+            first: self.last_starting_at_or_before(probe.start)
+                .filter(|interval| interval.overlaps_with(probe)),
+            rest: self.intervals
+                .range((Bound::Excluded(probe.start), Bound::Excluded(probe.end)))
+        }
+    }
+
+    fn last_starting_at_or_before(&self, value: T) -> Option<ContinuousInterval<T>> {
+        self.intervals
+            .range(..=value)
+            .next_back()
+            .map(|(&start, &end)| ContinuousInterval{start, end})
+    }
+}
+
+impl<'a, T: Ord + Copy> IntoIterator for &'a ContinuousIntervalSet<T> {
+    type Item = ContinuousInterval<T>;
+    type IntoIter = ContinuousIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Renders the covered values as a set of intervals, e.g. `{[0, 1.5), [2, 3)}`.
+impl<T: Ord + Copy + std::fmt::Display> std::fmt::Display for ContinuousIntervalSet<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{{")?;
+        for (i, interval) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{interval}")?;
+        }
+        write!(f, "}}")
+    }
+}
+
+/// Iterator over the intervals stored in a `ContinuousIntervalSet`,
+/// in ascending order.
+pub struct ContinuousIter<'a, T: Ord + Copy> {
+    inner: btree_map::Iter<'a, T, T>
+}
+
+impl<T: Ord + Copy> Iterator for ContinuousIter<'_, T> {
+    type Item = ContinuousInterval<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(&start, &end)| ContinuousInterval{start, end})
+    }
+}
+
+impl<T: Ord + Copy> DoubleEndedIterator for ContinuousIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(&start, &end)| ContinuousInterval{start, end})
+    }
+}
+
+/// Iterator over the intervals stored in a `ContinuousIntervalSet`
+/// which overlap a probe, in ascending order.
+pub struct ContinuousOverlapping<'a, T: Ord + Copy> {
+    /// The interval starting before the probe, if it reaches into it.
+    first: Option<ContinuousInterval<T>>,
+    rest: btree_map::Range<'a, T, T>
+}
+
+impl<T: Ord + Copy> Iterator for ContinuousOverlapping<'_, T> {
+    type Item = ContinuousInterval<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.first.take().or_else(|| {
+            self.rest.next().map(|(&start, &end)| ContinuousInterval{start, end})
+        })
+    }
+}
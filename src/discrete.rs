@@ -0,0 +1,173 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// A totally ordered type whose values can be enumerated one step at a time.
+///
+/// Implemented for all primitive integers, `char`, `Ipv4Addr` and `Ipv6Addr`.
+/// Implement it for your own types (e.g. newtype IDs or dates) to store
+/// them in an `Interval` or `AVLIntervalTree`.
+pub trait Discrete: Copy + Ord {
+    /// The smallest value of the type.
+    const MIN: Self;
+
+    /// The largest value of the type.
+    const MAX: Self;
+
+    /// The next larger value, or `None` for `Self::MAX`.
+    fn successor(&self) -> Option<Self>;
+
+    /// The next smaller value, or `None` for `Self::MIN`.
+    fn predecessor(&self) -> Option<Self>;
+
+    /// Number of successor steps from `start` to `stop`,
+    /// where `start` is not greater than `stop`.
+    fn steps_between(start: &Self, stop: &Self) -> u128;
+
+    /// The value `n` successor steps after this one,
+    /// or `None` if it does not exist.
+    fn forward(&self, n: u128) -> Option<Self>;
+}
+
+macro_rules! impl_discrete_signed {
+    ($($t:ty),*) => {
+        $(impl Discrete for $t {
+            const MIN: Self = <$t>::MIN;
+            const MAX: Self = <$t>::MAX;
+
+            fn successor(&self) -> Option<Self> {
+                self.checked_add(1)
+            }
+
+            fn predecessor(&self) -> Option<Self> {
+                self.checked_sub(1)
+            }
+
+            fn steps_between(start: &Self, stop: &Self) -> u128 {
+                (*stop as i128).abs_diff(*start as i128)
+            }
+
+            fn forward(&self, n: u128) -> Option<Self> {
+                let value = (*self as i128).checked_add_unsigned(n)?;
+                <$t>::try_from(value).ok()
+            }
+        })*
+    };
+}
+
+macro_rules! impl_discrete_unsigned {
+    ($($t:ty),*) => {
+        $(impl Discrete for $t {
+            const MIN: Self = <$t>::MIN;
+            const MAX: Self = <$t>::MAX;
+
+            fn successor(&self) -> Option<Self> {
+                self.checked_add(1)
+            }
+
+            fn predecessor(&self) -> Option<Self> {
+                self.checked_sub(1)
+            }
+
+            fn steps_between(start: &Self, stop: &Self) -> u128 {
+                (*stop as u128) - (*start as u128)
+            }
+
+            fn forward(&self, n: u128) -> Option<Self> {
+                let value = (*self as u128).checked_add(n)?;
+                <$t>::try_from(value).ok()
+            }
+        })*
+    };
+}
+
+impl_discrete_signed!(i8, i16, i32, i64, i128, isize);
+impl_discrete_unsigned!(u8, u16, u32, u64, u128, usize);
+
+/// Start of the surrogate range, which contains no `char` values.
+const SURROGATE_START: u32 = 0xD800;
+/// Number of code points in the surrogate range.
+const SURROGATE_LEN: u32 = 0x800;
+
+/// Position of a `char` when skipping the surrogate range.
+fn char_index(value: char) -> u32 {
+    let code = value as u32;
+    if code < SURROGATE_START {
+        code
+    } else {
+        code - SURROGATE_LEN
+    }
+}
+
+/// Inverse of `char_index`.
+fn char_from_index(index: u32) -> Option<char> {
+    if index < SURROGATE_START {
+        char::from_u32(index)
+    } else {
+        char::from_u32(index.checked_add(SURROGATE_LEN)?)
+    }
+}
+
+impl Discrete for char {
+    const MIN: Self = '\0';
+    const MAX: Self = char::MAX;
+
+    fn successor(&self) -> Option<Self> {
+        char_from_index(char_index(*self) + 1)
+    }
+
+    fn predecessor(&self) -> Option<Self> {
+        char_from_index(char_index(*self).checked_sub(1)?)
+    }
+
+    fn steps_between(start: &Self, stop: &Self) -> u128 {
+        (char_index(*stop) - char_index(*start)) as u128
+    }
+
+    fn forward(&self, n: u128) -> Option<Self> {
+        let n = u32::try_from(n).ok()?;
+        char_from_index(char_index(*self).checked_add(n)?)
+    }
+}
+
+impl Discrete for Ipv4Addr {
+    const MIN: Self = Ipv4Addr::new(0, 0, 0, 0);
+    const MAX: Self = Ipv4Addr::new(255, 255, 255, 255);
+
+    fn successor(&self) -> Option<Self> {
+        u32::from(*self).successor().map(Self::from)
+    }
+
+    fn predecessor(&self) -> Option<Self> {
+        u32::from(*self).predecessor().map(Self::from)
+    }
+
+    fn steps_between(start: &Self, stop: &Self) -> u128 {
+        u32::steps_between(&u32::from(*start), &u32::from(*stop))
+    }
+
+    fn forward(&self, n: u128) -> Option<Self> {
+        u32::from(*self).forward(n).map(Self::from)
+    }
+}
+
+impl Discrete for Ipv6Addr {
+    const MIN: Self = Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0);
+    const MAX: Self = Ipv6Addr::new(
+        0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF
+    );
+
+    fn successor(&self) -> Option<Self> {
+        u128::from(*self).successor().map(Self::from)
+    }
+
+    fn predecessor(&self) -> Option<Self> {
+        u128::from(*self).predecessor().map(Self::from)
+    }
+
+    fn steps_between(start: &Self, stop: &Self) -> u128 {
+        u128::steps_between(&u128::from(*start), &u128::from(*stop))
+    }
+
+    fn forward(&self, n: u128) -> Option<Self> {
+        u128::from(*self).forward(n).map(Self::from)
+    }
+}
@@ -1,30 +1,120 @@
-use std::error::Error;
 use std::fmt::Formatter;
-
-
-#[derive(Copy, Clone, Debug)]
-pub struct Interval<T: num::PrimInt + std::fmt::Display> {
+use std::ops::{Bound, Range, RangeBounds, RangeInclusive};
+use crate::discrete::Discrete;
+
+
+/// A non-empty interval of discrete values.
+///
+/// Intervals are stored as closed `[start, stop]` ranges. Half-open,
+/// open and unbounded intervals are converted to the closed interval
+/// containing exactly the same values when they are created, so
+/// all operations respect the kinds of their endpoints.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Interval<T: Discrete> {
     start: T,
     stop: T
 }
 
 #[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
 pub enum IntervalError {
     #[error("Cannot merge non-overlapping/non-adjacent intervals")]
-    MergeOnDisjointIntervals
+    MergeOnDisjointIntervals,
+    #[error("Cannot create an interval whose start is greater than its stop")]
+    Inverted,
+    #[error("Cannot create an interval which contains no values")]
+    Empty,
+    #[error("Failed to write tree: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to format tree: {0}")]
+    Fmt(#[from] std::fmt::Error)
 }
 
-impl<T: num::PrimInt + std::fmt::Display> std::fmt::Display for Interval<T> {
+impl<T: Discrete + std::fmt::Display> std::fmt::Display for Interval<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "[{}, {}]", self.start, self.stop)
     }
 }
 
-impl<T: num::PrimInt + std::fmt::Display> Interval<T> {
+impl<T: Discrete> Interval<T> {
+    /// Create the interval `[start, stop]`.
+    ///
+    /// `start` must not be greater than `stop`;
+    /// this is only checked in debug builds.
     pub fn new(start: T, stop: T) -> Self {
+        debug_assert!(start <= stop, "interval start is greater than its stop");
         Self{start, stop}
     }
 
+    /// Create the interval `[start, stop]`, or fail if
+    /// `start` is greater than `stop`.
+    pub fn try_new(start: T, stop: T) -> Result<Self, IntervalError> {
+        if start <= stop {
+            Ok(Self{start, stop})
+        } else {
+            Err(IntervalError::Inverted)
+        }
+    }
+
+    /// Create the interval between two values given in any order.
+    pub fn from_unordered(a: T, b: T) -> Self {
+        Self{start: a.min(b), stop: a.max(b)}
+    }
+
+    /// Create the interval containing every value within `start` and `end`,
+    /// or fail if there is no such value.
+    pub fn from_bounds(start: Bound<T>, end: Bound<T>) -> Result<Self, IntervalError> {
+        let start = match start {
+            Bound::Included(start) => Some(start),
+            Bound::Excluded(start) => start.successor(),
+            Bound::Unbounded => Some(T::MIN)
+        };
+        let stop = match end {
+            Bound::Included(stop) => Some(stop),
+            Bound::Excluded(stop) => stop.predecessor(),
+            Bound::Unbounded => Some(T::MAX)
+        };
+        match (start, stop) {
+            (Some(start), Some(stop)) if start <= stop => Ok(Self{start, stop}),
+            _ => Err(IntervalError::Empty)
+        }
+    }
+
+    /// Create the half-open interval `[start, stop)`.
+    pub fn half_open(start: T, stop: T) -> Result<Self, IntervalError> {
+        Self::from_bounds(Bound::Included(start), Bound::Excluded(stop))
+    }
+
+    /// Create the open interval `(start, stop)`.
+    pub fn open(start: T, stop: T) -> Result<Self, IntervalError> {
+        Self::from_bounds(Bound::Excluded(start), Bound::Excluded(stop))
+    }
+
+    /// Create the interval `[start, ∞)`.
+    pub fn at_least(start: T) -> Self {
+        Self{start, stop: T::MAX}
+    }
+
+    /// Create the interval `(start, ∞)`.
+    pub fn greater_than(start: T) -> Result<Self, IntervalError> {
+        Self::from_bounds(Bound::Excluded(start), Bound::Unbounded)
+    }
+
+    /// Create the interval `(-∞, stop]`.
+    pub fn at_most(stop: T) -> Self {
+        Self{start: T::MIN, stop}
+    }
+
+    /// Create the interval `(-∞, stop)`.
+    pub fn less_than(stop: T) -> Result<Self, IntervalError> {
+        Self::from_bounds(Bound::Unbounded, Bound::Excluded(stop))
+    }
+
+    /// Create the interval containing every value of `T`.
+    pub fn unbounded() -> Self {
+        Self{start: T::MIN, stop: T::MAX}
+    }
+
     pub fn start(&self) -> T {
         self.start
     }
@@ -33,6 +123,23 @@ impl<T: num::PrimInt + std::fmt::Display> Interval<T> {
         self.stop
     }
 
+    /// Number of values contained in the interval.
+    ///
+    /// Saturates at `u128::MAX` for intervals spanning
+    /// the full domain of a 128-bit type.
+    pub fn size(&self) -> u128 {
+        T::steps_between(&self.start, &self.stop).saturating_add(1)
+    }
+
+    /// Return the value at offset `n` from the start of the interval,
+    /// or `None` if the interval contains no more than `n` values.
+    pub fn nth(&self, n: u128) -> Option<T> {
+        if n >= self.size() {
+            return None;
+        }
+        self.start.forward(n)
+    }
+
     pub fn contains_value(&self, value: T) -> bool {
         self.start <= value && value <= self.stop
     }
@@ -49,11 +156,11 @@ impl<T: num::PrimInt + std::fmt::Display> Interval<T> {
     }
 
     pub fn left_adjacent_to(&self, other: &Interval<T>) -> bool {
-        self.stop + T::one() == other.start
+        self.stop.successor() == Some(other.start)
     }
 
     pub fn right_adjacent_to(&self, other: &Interval<T>) -> bool {
-        self.start == other.stop + T::one()
+        other.left_adjacent_to(self)
     }
 
     pub fn adjacent_to(&self, other: &Interval<T>) -> bool {
@@ -64,11 +171,11 @@ impl<T: num::PrimInt + std::fmt::Display> Interval<T> {
         self.overlaps_with(other) || self.adjacent_to(other)
     }
 
-    pub fn merge(&self, other: &Interval<T>) -> Result<Interval<T>, Box<dyn Error>> {
+    pub fn merge(&self, other: &Interval<T>) -> Result<Interval<T>, IntervalError> {
         if self.can_merge_with(other) {
             Ok(self.merge_unchecked(other))
         } else {
-            Err(IntervalError::MergeOnDisjointIntervals.into())
+            Err(IntervalError::MergeOnDisjointIntervals)
         }
     }
 
@@ -79,12 +186,12 @@ impl<T: num::PrimInt + std::fmt::Display> Interval<T> {
         }
     }
 
-    pub  fn merge_inplace(&mut self, other: &Interval<T>) -> Result<(), Box<dyn Error>> {
+    pub fn merge_inplace(&mut self, other: &Interval<T>) -> Result<(), IntervalError> {
         if self.can_merge_with(other) {
             self.merge_inplace_unchecked(other);
             Ok(())
         } else {
-            Err(IntervalError::MergeOnDisjointIntervals.into())
+            Err(IntervalError::MergeOnDisjointIntervals)
         }
     }
 
@@ -101,3 +208,43 @@ impl<T: num::PrimInt + std::fmt::Display> Interval<T> {
         self.start > other.stop
     }
 }
+
+/// Panics if the range is empty, which includes ranges
+/// that have been iterated to the end.
+impl<T: Discrete> From<RangeInclusive<T>> for Interval<T> {
+    fn from(range: RangeInclusive<T>) -> Self {
+        assert!(!range.is_empty(), "cannot create an interval from an empty range");
+        let (start, stop) = range.into_inner();
+        Self{start, stop}
+    }
+}
+
+impl<T: Discrete> TryFrom<Range<T>> for Interval<T> {
+    type Error = IntervalError;
+
+    fn try_from(range: Range<T>) -> Result<Self, Self::Error> {
+        Self::half_open(range.start, range.end)
+    }
+}
+
+impl<T: Discrete> From<&Interval<T>> for Interval<T> {
+    fn from(interval: &Interval<T>) -> Self {
+        *interval
+    }
+}
+
+impl<T: Discrete> From<Interval<T>> for RangeInclusive<T> {
+    fn from(interval: Interval<T>) -> Self {
+        interval.start..=interval.stop
+    }
+}
+
+impl<T: Discrete> RangeBounds<T> for Interval<T> {
+    fn start_bound(&self) -> Bound<&T> {
+        Bound::Included(&self.start)
+    }
+
+    fn end_bound(&self) -> Bound<&T> {
+        Bound::Included(&self.stop)
+    }
+}
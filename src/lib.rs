@@ -1,30 +1,35 @@
+mod discrete;
 mod traits;
 mod interval;
 mod avl_tree;
+mod continuous;
 
 
-pub use interval::Interval;
+pub use discrete::Discrete;
+pub use interval::{Interval, IntervalError};
 pub use traits::IntervalTree;
-pub use avl_tree::AVLIntervalTree;
+pub use continuous::{ContinuousInterval, ContinuousIntervalSet, ContinuousIter, ContinuousOverlapping};
+pub use avl_tree::{AVLIntervalTree, AVLIntervalMap, Iter, IntoIter, Range, ClippedRange, Values, Gaps, MapIter};
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashSet;
+    use std::cell::Cell;
+    use std::collections::{HashMap, HashSet};
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    use std::net::{Ipv4Addr, Ipv6Addr};
     use std::error::Error;
+    use std::ops::{Bound, RangeBounds, RangeInclusive};
+    use std::rc::Rc;
     use rand::{thread_rng, Rng};
     use super::*;
 
     fn random_interval<T: Rng>(rng: &mut T) -> Interval<i8> {
-        let start = rng.gen::<i8>();
-        let stop = rng.gen::<i8>();
-        Interval::new(
-            i8::min(start, stop),
-            i8::max(start, stop)
-        )
+        Interval::from_unordered(rng.gen::<i8>(), rng.gen::<i8>())
     }
 
     fn random_interval_small<T: Rng>(rng: &mut T) -> Interval<i8> {
-        let mut start = rng.gen::<i8>();
+        let start = rng.gen::<i8>();
         let (start, stop) = if start >= i8::MAX - 5 {
             (start - 5, start)
         } else {
@@ -95,7 +100,7 @@ mod tests {
                 for x in interval.start()..=interval.stop() {
                     items_in_tree.remove(&x);
                 }
-                tree.delete(&interval);
+                tree.delete(interval);
                 println!("Delete: {}", interval);
             }
             assert!(tree.is_avl());
@@ -107,6 +112,1035 @@ mod tests {
         Ok(())
     }
 
+    fn assert_iter_matches_set(tree: &AVLIntervalTree<i8>, items_in_tree: &HashSet<i8>) {
+        let intervals: Vec<Interval<i8>> = tree.iter().collect();
+        for pair in intervals.windows(2) {
+            assert!(pair[0].is_left_of(&pair[1]));
+            assert!(!pair[0].can_merge_with(&pair[1]));
+        }
+        let mut items_in_iter = HashSet::new();
+        for interval in &intervals {
+            items_in_iter.extend(interval.start()..=interval.stop());
+        }
+        assert_eq!(&items_in_iter, items_in_tree);
+        assert_eq!(intervals.len() as i32, tree.number_of_nodes());
+    }
+
+    #[test]
+    fn random_test_avl_tree_iter() {
+        let mut tree: AVLIntervalTree<i8> = AVLIntervalTree::empty();
+        let mut items_in_tree: HashSet<i8> = HashSet::new();
+        let mut rng = thread_rng();
+        const ITERATIONS: i32 = 1000;
+        for _ in 0..ITERATIONS {
+            let interval = random_interval_small(&mut rng);
+            if rng.gen_bool(0.6) {
+                items_in_tree.extend(interval.start()..=interval.stop());
+                tree.insert(interval);
+            } else {
+                for x in interval.start()..=interval.stop() {
+                    items_in_tree.remove(&x);
+                }
+                tree.delete(interval);
+            }
+            assert_iter_matches_set(&tree, &items_in_tree);
+        }
+        let borrowed: Vec<Interval<i8>> = (&tree).into_iter().collect();
+        let owned: Vec<Interval<i8>> = tree.into_iter().collect();
+        assert_eq!(borrowed.len(), owned.len());
+        for (left, right) in borrowed.iter().zip(&owned) {
+            assert_eq!((left.start(), left.stop()), (right.start(), right.stop()));
+        }
+    }
+
+    fn random_bounds<T: Rng>(rng: &mut T) -> (Bound<i8>, Bound<i8>) {
+        let interval = random_interval(rng);
+        let start = match rng.gen_range(0..3) {
+            0 => Bound::Included(interval.start()),
+            1 => Bound::Excluded(interval.start()),
+            _ => Bound::Unbounded
+        };
+        let stop = match rng.gen_range(0..3) {
+            0 => Bound::Included(interval.stop()),
+            1 => Bound::Excluded(interval.stop()),
+            _ => Bound::Unbounded
+        };
+        (start, stop)
+    }
+
+    fn as_pairs<I: IntoIterator<Item=Interval<i8>>>(intervals: I) -> Vec<(i8, i8)> {
+        intervals.into_iter().map(|i| (i.start(), i.stop())).collect()
+    }
+
+    #[test]
+    fn random_test_avl_tree_range() {
+        let mut tree: AVLIntervalTree<i8> = AVLIntervalTree::empty();
+        let mut rng = thread_rng();
+        const ITERATIONS: i32 = 200;
+        const SAMPLES_PER_ITERATION: i32 = 20;
+        for _ in 0..ITERATIONS {
+            tree.insert(random_interval_small(&mut rng));
+            for _ in 0..SAMPLES_PER_ITERATION {
+                let bounds = random_bounds(&mut rng);
+                let expected: Vec<Interval<i8>> = tree.iter()
+                    .filter(|i| (i.start()..=i.stop()).any(|x| bounds.contains(&x)))
+                    .collect();
+                assert_eq!(as_pairs(tree.range(bounds)), as_pairs(expected.iter().copied()));
+                assert_eq!(
+                    as_pairs(tree.range(bounds).rev()),
+                    as_pairs(expected.iter().rev().copied())
+                );
+                let clipped: Vec<(i8, i8)> = expected.iter()
+                    .map(|i| {
+                        let mut values = (i.start()..=i.stop()).filter(|x| bounds.contains(x));
+                        let first = values.next().unwrap();
+                        (first, values.next_back().unwrap_or(first))
+                    })
+                    .collect();
+                assert_eq!(as_pairs(tree.range_clipped(bounds)), clipped);
+
+                // Alternate between both ends
+                let mut range = tree.range(bounds);
+                let mut front = Vec::new();
+                let mut back = Vec::new();
+                loop {
+                    let next = if rng.gen_bool(0.5) {
+                        range.next().map(|i| front.push(i))
+                    } else {
+                        range.next_back().map(|i| back.push(i))
+                    };
+                    if next.is_none() {
+                        break;
+                    }
+                }
+                assert!(range.next().is_none() && range.next_back().is_none());
+                front.extend(back.into_iter().rev());
+                assert_eq!(as_pairs(front), as_pairs(expected));
+            }
+        }
+    }
+
+    #[test]
+    fn random_test_avl_tree_values() {
+        let mut tree: AVLIntervalTree<i8> = AVLIntervalTree::empty();
+        let mut items_in_tree: HashSet<i8> = HashSet::new();
+        let mut rng = thread_rng();
+        const ITERATIONS: i32 = 200;
+        for _ in 0..ITERATIONS {
+            let interval = random_interval(&mut rng);
+            if rng.gen_bool(0.6) {
+                items_in_tree.extend(interval.start()..=interval.stop());
+                tree.insert(interval);
+            } else {
+                for x in interval.start()..=interval.stop() {
+                    items_in_tree.remove(&x);
+                }
+                tree.delete(interval);
+            }
+            let mut expected: Vec<i8> = items_in_tree.iter().copied().collect();
+            expected.sort();
+            let mut values = tree.values();
+            assert_eq!(values.len(), expected.len());
+            values.next();
+            assert_eq!(values.len(), expected.len().saturating_sub(1));
+            assert_eq!(tree.values().collect::<Vec<i8>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_values_at_type_bounds() {
+        let mut tree: AVLIntervalTree<u8> = AVLIntervalTree::empty();
+        tree.insert(Interval::new(250, u8::MAX));
+        tree.insert_value(0);
+        assert_eq!(tree.values().collect::<Vec<u8>>(), vec![0, 250, 251, 252, 253, 254, 255]);
+        tree.insert(Interval::new(u8::MIN, u8::MAX));
+        assert_eq!(tree.values().len(), 256);
+        assert_eq!(tree.values().count(), 256);
+
+        let mut tree: AVLIntervalTree<u64> = AVLIntervalTree::empty();
+        tree.insert(Interval::new(u64::MAX - 2, u64::MAX));
+        assert_eq!(tree.values().size_hint(), (3, Some(3)));
+        assert_eq!(tree.values().collect::<Vec<u64>>(), vec![u64::MAX - 2, u64::MAX - 1, u64::MAX]);
+    }
+
+    fn random_tree<T: Rng>(rng: &mut T) -> (AVLIntervalTree<i8>, HashSet<i8>) {
+        let mut tree: AVLIntervalTree<i8> = AVLIntervalTree::empty();
+        let mut items_in_tree: HashSet<i8> = HashSet::new();
+        for _ in 0..rng.gen_range(0..20) {
+            let interval = random_interval_small(rng);
+            if rng.gen_bool(0.7) {
+                items_in_tree.extend(interval.start()..=interval.stop());
+                tree.insert(interval);
+            } else {
+                for x in interval.start()..=interval.stop() {
+                    items_in_tree.remove(&x);
+                }
+                tree.delete(interval);
+            }
+        }
+        (tree, items_in_tree)
+    }
+
+    fn assert_tree_matches_set(tree: &AVLIntervalTree<i8>, items_in_tree: &HashSet<i8>) {
+        assert!(tree.is_avl());
+        assert_iter_matches_set(tree, items_in_tree);
+    }
+
+    #[test]
+    fn random_test_avl_tree_union() {
+        let mut rng = thread_rng();
+        const ITERATIONS: i32 = 200;
+        for _ in 0..ITERATIONS {
+            let (left, left_items) = random_tree(&mut rng);
+            let (right, right_items) = random_tree(&mut rng);
+            let expected: HashSet<i8> = left_items.union(&right_items).copied().collect();
+            assert_tree_matches_set(&left.union(&right), &expected);
+            assert_tree_matches_set(&(&left | &right), &expected);
+            let mut tree = left;
+            tree |= &right;
+            assert_tree_matches_set(&tree, &expected);
+            tree.union_with(&right);
+            assert_tree_matches_set(&tree, &expected);
+        }
+    }
+
+    #[test]
+    fn random_test_avl_tree_intersection() {
+        let mut rng = thread_rng();
+        const ITERATIONS: i32 = 200;
+        for _ in 0..ITERATIONS {
+            let (left, left_items) = random_tree(&mut rng);
+            let (right, right_items) = random_tree(&mut rng);
+            let expected: HashSet<i8> = left_items.intersection(&right_items).copied().collect();
+            assert_tree_matches_set(&left.intersection(&right), &expected);
+            assert_tree_matches_set(&(&left & &right), &expected);
+            let mut tree = left;
+            tree &= &right;
+            assert_tree_matches_set(&tree, &expected);
+            tree.intersect_with(&right);
+            assert_tree_matches_set(&tree, &expected);
+        }
+    }
+
+    #[test]
+    fn random_test_avl_tree_difference() {
+        let mut rng = thread_rng();
+        const ITERATIONS: i32 = 200;
+        for _ in 0..ITERATIONS {
+            let (left, left_items) = random_tree(&mut rng);
+            let (right, right_items) = random_tree(&mut rng);
+            let expected: HashSet<i8> = left_items.difference(&right_items).copied().collect();
+            assert_tree_matches_set(&left.difference(&right), &expected);
+            assert_tree_matches_set(&(&left - &right), &expected);
+            let mut tree = left.clone();
+            tree -= &right;
+            assert_tree_matches_set(&tree, &expected);
+            tree.difference_with(&right);
+            assert_tree_matches_set(&tree, &expected);
+
+            let expected: HashSet<i8> = left_items.symmetric_difference(&right_items).copied().collect();
+            assert_tree_matches_set(&left.symmetric_difference(&right), &expected);
+            assert_tree_matches_set(&(&left ^ &right), &expected);
+            let mut tree = left;
+            tree ^= &right;
+            assert_tree_matches_set(&tree, &expected);
+            tree.symmetric_difference_with(&right);
+            assert_tree_matches_set(&tree, &left_items);
+        }
+    }
+
+    #[test]
+    fn random_test_avl_tree_complement() {
+        let mut rng = thread_rng();
+        const ITERATIONS: i32 = 200;
+        for _ in 0..ITERATIONS {
+            let (tree, items_in_tree) = random_tree(&mut rng);
+            let expected: HashSet<i8> = (i8::MIN..=i8::MAX)
+                .filter(|x| !items_in_tree.contains(x))
+                .collect();
+            assert_tree_matches_set(&tree.complement(), &expected);
+
+            let bounds = random_interval(&mut rng);
+            let expected: HashSet<i8> = (bounds.start()..=bounds.stop())
+                .filter(|x| !items_in_tree.contains(x))
+                .collect();
+            assert_tree_matches_set(&tree.complement_within(bounds), &expected);
+        }
+    }
+
+    #[test]
+    fn random_test_avl_tree_gaps() {
+        let mut rng = thread_rng();
+        const ITERATIONS: i32 = 200;
+        for _ in 0..ITERATIONS {
+            let (tree, _) = random_tree(&mut rng);
+            let complement = tree.complement();
+            assert_eq!(as_pairs(tree.gaps()), as_pairs(complement.iter()));
+
+            let bounds = random_interval(&mut rng);
+            let complement = tree.complement_within(bounds);
+            assert_eq!(as_pairs(tree.gaps_in(bounds)), as_pairs(complement.iter()));
+            for gap in tree.gaps_in(bounds) {
+                assert!(bounds.contains_interval(&gap));
+                assert!(tree.range(gap.start()..=gap.stop()).next().is_none());
+            }
+        }
+
+        let mut tree: AVLIntervalTree<u8> = AVLIntervalTree::empty();
+        tree.insert(Interval::new(0, 9));
+        tree.insert(Interval::new(250, 255));
+        let gaps: Vec<(u8, u8)> = tree.gaps().map(|i| (i.start(), i.stop())).collect();
+        assert_eq!(gaps, vec![(10, 249)]);
+        tree.insert(Interval::new(0, 255));
+        assert!(tree.gaps().next().is_none());
+    }
+
+    #[test]
+    fn random_test_avl_tree_predicates() {
+        let mut rng = thread_rng();
+        const ITERATIONS: i32 = 500;
+        for _ in 0..ITERATIONS {
+            let (left, left_items) = random_tree(&mut rng);
+            let (mut right, mut right_items) = random_tree(&mut rng);
+            if rng.gen_bool(0.3) {
+                right.union_with(&left);
+                right_items.extend(&left_items);
+            }
+            assert_eq!(left.is_subset(&right), left_items.is_subset(&right_items));
+            assert_eq!(left.is_superset(&right), left_items.is_superset(&right_items));
+            assert_eq!(left.is_disjoint(&right), left_items.is_disjoint(&right_items));
+            assert_eq!(left.overlaps(&right), !left_items.is_disjoint(&right_items));
+        }
+    }
+
+    fn hash_of<H: Hash>(value: &H) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn random_test_avl_tree_equality() {
+        let mut rng = thread_rng();
+        const ITERATIONS: i32 = 500;
+        for _ in 0..ITERATIONS {
+            let (left, left_items) = random_tree(&mut rng);
+            let (right, right_items) = random_tree(&mut rng);
+            assert_eq!(left == right, left_items == right_items);
+
+            // Rebuild the same set through a different sequence of operations
+            let mut rebuilt: AVLIntervalTree<i8> = AVLIntervalTree::empty();
+            rebuilt.insert(Interval::new(i8::MIN, i8::MAX));
+            for gap in left.gaps() {
+                rebuilt.delete(gap);
+            }
+            assert!(left == rebuilt);
+            assert_eq!(hash_of(&left), hash_of(&rebuilt));
+
+            // A clone is independent of the original
+            let mut cloned = left.clone();
+            assert!(cloned == left);
+            cloned.delete(Interval::new(i8::MIN, i8::MAX));
+            assert!(cloned == AVLIntervalTree::default());
+            assert_eq!(left.values().collect::<HashSet<_>>(), left_items);
+        }
+    }
+
+    #[test]
+    fn random_test_avl_tree_overlapping() {
+        let mut rng = thread_rng();
+        const ITERATIONS: i32 = 200;
+        const SAMPLES_PER_ITERATION: i32 = 20;
+        for _ in 0..ITERATIONS {
+            let (tree, _) = random_tree(&mut rng);
+            for _ in 0..SAMPLES_PER_ITERATION {
+                let probe = if rng.gen_bool(0.5) {
+                    random_interval(&mut rng)
+                } else {
+                    random_interval_small(&mut rng)
+                };
+                let expected: Vec<Interval<i8>> = tree.iter()
+                    .filter(|interval| interval.overlaps_with(&probe))
+                    .collect();
+                assert_eq!(tree.overlapping(&probe).collect::<Vec<_>>(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn random_test_avl_tree_find_containing() {
+        let mut rng = thread_rng();
+        const ITERATIONS: i32 = 200;
+        const SAMPLES_PER_ITERATION: i32 = 20;
+        for _ in 0..ITERATIONS {
+            let (tree, _) = random_tree(&mut rng);
+            for _ in 0..SAMPLES_PER_ITERATION {
+                let value: i8 = rng.gen();
+                let expected = tree.iter().find(|interval| interval.contains_value(value));
+                assert_eq!(tree.find_containing(value), expected);
+
+                let probe = random_interval_small(&mut rng);
+                let expected = tree.iter().find(|interval| interval.contains_interval(&probe));
+                assert_eq!(tree.find_containing_interval(&probe), expected);
+                assert_eq!(tree.contains(&probe), expected.is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn random_test_avl_tree_navigation() {
+        let mut rng = thread_rng();
+        const ITERATIONS: i32 = 200;
+        const SAMPLES_PER_ITERATION: i32 = 20;
+        for _ in 0..ITERATIONS {
+            let (tree, _) = random_tree(&mut rng);
+            let intervals: Vec<Interval<i8>> = tree.iter().collect();
+            assert_eq!(tree.first(), intervals.first().copied());
+            assert_eq!(tree.last(), intervals.last().copied());
+            for _ in 0..SAMPLES_PER_ITERATION {
+                let value: i8 = rng.gen();
+                let next = intervals.iter().copied().find(|i| i.start() > value);
+                let prev = intervals.iter().copied().rev().find(|i| i.stop() < value);
+                assert_eq!(tree.next_after(value), next);
+                assert_eq!(tree.prev_before(value), prev);
+
+                let distance = |i: &Interval<i8>| if i.contains_value(value) {
+                    0
+                } else {
+                    i16::min(
+                        (i.start() as i16 - value as i16).abs(),
+                        (i.stop() as i16 - value as i16).abs()
+                    )
+                };
+                let nearest = intervals.iter().copied().min_by_key(distance);
+                assert_eq!(tree.nearest(value), nearest);
+            }
+        }
+    }
+
+    #[test]
+    fn random_test_avl_tree_covered_len() {
+        let mut tree: AVLIntervalTree<i8> = AVLIntervalTree::empty();
+        let mut items_in_tree: HashSet<i8> = HashSet::new();
+        let mut rng = thread_rng();
+        const ITERATIONS: i32 = 500;
+        const SAMPLES_PER_ITERATION: i32 = 10;
+        for _ in 0..ITERATIONS {
+            let interval = random_interval_small(&mut rng);
+            if rng.gen_bool(0.6) {
+                items_in_tree.extend(interval.start()..=interval.stop());
+                tree.insert(interval);
+            } else {
+                for x in interval.start()..=interval.stop() {
+                    items_in_tree.remove(&x);
+                }
+                tree.delete(interval);
+            }
+            assert_eq!(tree.covered_len(), items_in_tree.len() as u128);
+            for _ in 0..SAMPLES_PER_ITERATION {
+                let bounds = random_interval(&mut rng);
+                let expected = (bounds.start()..=bounds.stop())
+                    .filter(|x| items_in_tree.contains(x))
+                    .count();
+                assert_eq!(tree.covered_len_in(bounds), expected as u128);
+            }
+        }
+
+        let (left, left_items) = random_tree(&mut rng);
+        let (right, right_items) = random_tree(&mut rng);
+        let union = left.union(&right);
+        assert_eq!(union.covered_len(), left_items.union(&right_items).count() as u128);
+
+        let mut tree: AVLIntervalTree<u64> = AVLIntervalTree::empty();
+        tree.insert(Interval::new(u64::MIN, u64::MAX));
+        assert_eq!(tree.covered_len(), u64::MAX as u128 + 1);
+        assert_eq!(tree.covered_len_in(Interval::new(u64::MAX - 9, u64::MAX)), 10);
+    }
+
+    #[test]
+    fn random_test_avl_tree_rank_select() {
+        let mut rng = thread_rng();
+        const ITERATIONS: i32 = 200;
+        for _ in 0..ITERATIONS {
+            let (tree, _) = random_tree(&mut rng);
+            let values: Vec<i8> = tree.values().collect();
+            for x in i8::MIN..=i8::MAX {
+                let expected = values.iter().filter(|&&value| value < x).count();
+                assert_eq!(tree.rank(x), expected as u128);
+            }
+            for (k, &value) in values.iter().enumerate() {
+                assert_eq!(tree.select(k as u128), Some(value));
+                assert_eq!(tree.rank(value), k as u128);
+            }
+            assert_eq!(tree.select(values.len() as u128), None);
+        }
+
+        let mut tree: AVLIntervalTree<u128> = AVLIntervalTree::empty();
+        tree.insert(Interval::new(0, 9));
+        tree.insert(Interval::new(u128::MAX - 9, u128::MAX));
+        assert_eq!(tree.select(19), Some(u128::MAX));
+        assert_eq!(tree.rank(u128::MAX), 19);
+
+        let mut tree: AVLIntervalTree<i128> = AVLIntervalTree::empty();
+        tree.insert(Interval::new(i128::MIN, i128::MIN + 4));
+        tree.insert(Interval::new(i128::MAX - 4, i128::MAX));
+        assert_eq!(tree.select(0), Some(i128::MIN));
+        assert_eq!(tree.select(9), Some(i128::MAX));
+        assert_eq!(tree.rank(i128::MAX), 9);
+    }
+
+    fn runs_of(values: &[i8]) -> Vec<Interval<i8>> {
+        let mut runs: Vec<Interval<i8>> = Vec::new();
+        for &x in values {
+            match runs.last_mut() {
+                Some(last) if last.stop() as i16 + 1 == x as i16 => {
+                    *last = Interval::new(last.start(), x);
+                }
+                _ => runs.push(Interval::new(x, x))
+            }
+        }
+        runs
+    }
+
+    #[test]
+    fn random_test_avl_tree_reporting() {
+        let mut tree: AVLIntervalTree<i8> = AVLIntervalTree::empty();
+        let mut items_in_tree: HashSet<i8> = HashSet::new();
+        let mut rng = thread_rng();
+        const ITERATIONS: i32 = 1000;
+        for _ in 0..ITERATIONS {
+            let interval = random_interval_small(&mut rng);
+            if rng.gen_bool(0.6) {
+                let added: Vec<i8> = (interval.start()..=interval.stop())
+                    .filter(|&x| items_in_tree.insert(x))
+                    .collect();
+                assert_eq!(tree.insert_reporting(interval), runs_of(&added));
+            } else {
+                let removed: Vec<i8> = (interval.start()..=interval.stop())
+                    .filter(|x| items_in_tree.remove(x))
+                    .collect();
+                assert_eq!(tree.delete_reporting(interval), runs_of(&removed));
+            }
+            assert_tree_matches_set(&tree, &items_in_tree);
+        }
+    }
+
+    macro_rules! test_type_bounds {
+        ($($name:ident: $t:ty,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let min = <$t>::MIN;
+                    let max = <$t>::MAX;
+                    let one: $t = 1;
+                    let full = Interval::new(min, max);
+                    let low = Interval::new(min, min + one);
+                    let high = Interval::new(max - one, max);
+
+                    // Discrete steps
+                    assert_eq!(Discrete::successor(&max), None);
+                    assert_eq!(Discrete::predecessor(&min), None);
+                    assert_eq!(Discrete::successor(&min), Some(min + one));
+                    assert_eq!(<$t>::steps_between(&(max - one), &max), 1);
+                    assert_eq!(min.forward(<$t>::steps_between(&min, &max)), Some(max));
+                    assert_eq!(max.forward(1), None);
+
+                    // Interval operations
+                    assert!(!high.left_adjacent_to(&low));
+                    assert!(!high.right_adjacent_to(&low));
+                    assert!(!low.adjacent_to(&high));
+                    assert!(Interval::new(min, min).left_adjacent_to(&Interval::new(min + one, max)));
+                    assert!(Interval::new(max, max).right_adjacent_to(&Interval::new(min, max - one)));
+                    assert!(full.contains_interval(&low) && full.contains_interval(&high));
+                    assert!(full.can_merge_with(&high));
+                    assert_eq!(low.size(), 2);
+                    assert_eq!(high.nth(1), Some(max));
+                    assert_eq!(high.nth(2), None);
+
+                    // Tree operations touching both bounds
+                    let mut tree: AVLIntervalTree<$t> = AVLIntervalTree::empty();
+                    tree.insert(high);
+                    tree.insert(low);
+                    assert!(tree.is_avl());
+                    assert_eq!(tree.number_of_nodes(), 2);
+                    assert!(tree.contains_value(max) && tree.contains_value(min));
+                    assert_eq!(tree.covered_len(), 4);
+                    assert_eq!(tree.values().collect::<Vec<$t>>(), vec![min, min + one, max - one, max]);
+                    assert_eq!(tree.rank(max), 3);
+                    assert_eq!(tree.select(3), Some(max));
+                    assert_eq!(tree.first(), Some(low));
+                    assert_eq!(tree.last(), Some(high));
+                    assert_eq!(tree.next_after(max), None);
+                    assert_eq!(tree.prev_before(min), None);
+                    assert_eq!(tree.nearest(max), Some(high));
+                    assert_eq!(tree.range(..).count(), 2);
+                    assert_eq!(tree.range((Bound::Excluded(max), Bound::Unbounded)).count(), 0);
+                    assert_eq!(tree.range(..min).count(), 0);
+                    assert_eq!(tree.range_clipped(max..).collect::<Vec<_>>(), vec![Interval::new(max, max)]);
+                    let gaps: Vec<Interval<$t>> = tree.gaps().collect();
+                    assert_eq!(gaps, vec![Interval::new(min + one + one, max - one - one)]);
+                    assert!(tree.complement().complement() == tree);
+
+                    // Splitting at the bounds
+                    tree.delete_value(max);
+                    tree.delete_value(min);
+                    let intervals: Vec<Interval<$t>> = tree.iter().collect();
+                    assert_eq!(intervals, vec![Interval::new(min + one, min + one), Interval::new(max - one, max - one)]);
+                    let gaps: Vec<Interval<$t>> = tree.gaps().collect();
+                    assert_eq!(tree.insert_reporting(full), gaps);
+                    assert_eq!(tree.number_of_nodes(), 1);
+                    assert!(tree.gaps().next().is_none());
+                    assert_eq!(tree.delete_reporting(&Interval::new(max, max)), vec![Interval::new(max, max)]);
+                    assert_eq!(tree.delete_reporting(&Interval::new(min, min)), vec![Interval::new(min, min)]);
+                    assert_eq!(tree.iter().collect::<Vec<_>>(), vec![Interval::new(min + one, max - one)]);
+                    tree.delete(full);
+                    assert!(tree.is_empty());
+                    assert_eq!(tree.gaps().collect::<Vec<_>>(), vec![full]);
+                }
+            )*
+        };
+    }
+
+    test_type_bounds! {
+        test_bounds_i8: i8,
+        test_bounds_i16: i16,
+        test_bounds_i32: i32,
+        test_bounds_i64: i64,
+        test_bounds_i128: i128,
+        test_bounds_isize: isize,
+        test_bounds_u8: u8,
+        test_bounds_u16: u16,
+        test_bounds_u32: u32,
+        test_bounds_u64: u64,
+        test_bounds_u128: u128,
+        test_bounds_usize: usize,
+    }
+
+    #[test]
+    fn test_interval_construction() {
+        assert_eq!(Interval::try_new(3, 7).unwrap(), Interval::new(3, 7));
+        assert_eq!(Interval::try_new(7, 7).unwrap(), Interval::new(7, 7));
+        assert!(matches!(Interval::try_new(7, 3), Err(IntervalError::Inverted)));
+        assert_eq!(Interval::from_unordered(7, 3), Interval::new(3, 7));
+        assert_eq!(Interval::from_unordered(3, 7), Interval::new(3, 7));
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn test_interval_new_inverted() {
+        Interval::new(7, 3);
+    }
+
+    #[test]
+    fn test_interval_merge_errors() {
+        fn assert_thread_safe<E: Error + Send + Sync + 'static>(_: &E) {}
+
+        let mut interval = Interval::new(0, 4);
+        assert_eq!(interval.merge(&Interval::new(5, 9)).unwrap(), Interval::new(0, 9));
+        let error = interval.merge(&Interval::new(6, 9)).unwrap_err();
+        assert!(matches!(error, IntervalError::MergeOnDisjointIntervals));
+        assert_thread_safe(&error);
+        assert!(matches!(
+            interval.merge_inplace(&Interval::new(6, 9)),
+            Err(IntervalError::MergeOnDisjointIntervals)
+        ));
+        interval.merge_inplace(&Interval::new(2, 9)).unwrap();
+        assert_eq!(interval, Interval::new(0, 9));
+    }
+
+    #[test]
+    fn test_avl_tree_output() -> Result<(), Box<dyn Error>> {
+        let mut tree: AVLIntervalTree<i8> = AVLIntervalTree::empty();
+        assert_eq!(tree.to_string(), "{}");
+        let mut text = String::new();
+        tree.write_tree_fmt(&mut text)?;
+        assert_eq!(text, "Tree()\n");
+
+        tree.insert(Interval::new(7, 9));
+        tree.insert(Interval::new(1, 3));
+        assert_eq!(tree.to_string(), "{[1, 3], [7, 9]}");
+
+        let mut text = String::new();
+        tree.write_tree_fmt(&mut text)?;
+        let mut bytes = Vec::new();
+        tree.write_tree(&mut bytes)?;
+        assert_eq!(String::from_utf8(bytes)?, text);
+        assert!(text.starts_with("Tree(\n  Node(\n"));
+        assert!(text.contains("interval=[7, 9]") && text.contains("interval=[1, 3]"));
+
+        // I/O errors are passed through rather than reported as formatting errors
+        let mut buffer = [0u8; 8];
+        assert!(matches!(tree.write_tree(&mut buffer.as_mut_slice()), Err(IntervalError::Io(_))));
+        assert_eq!(&buffer, b"Tree(\n  ");
+
+        let debug = format!("{tree:?}");
+        assert!(debug.contains("AVLNode") && debug.contains("height: 2"));
+        Ok(())
+    }
+
+    #[test]
+    fn random_test_interval_bounds() {
+        let mut rng = thread_rng();
+        const ITERATIONS: i32 = 1000;
+        for _ in 0..ITERATIONS {
+            let (start, end) = random_bounds(&mut rng);
+            let start = if rng.gen_bool(0.2) { Bound::Excluded(i8::MAX) } else { start };
+            let expected: Vec<i8> = (i8::MIN..=i8::MAX)
+                .filter(|x| (start, end).contains(x))
+                .collect();
+            match Interval::from_bounds(start, end) {
+                Ok(interval) => {
+                    let values: Vec<i8> = (i8::MIN..=i8::MAX)
+                        .filter(|&x| interval.contains_value(x))
+                        .collect();
+                    assert_eq!(values, expected);
+                }
+                Err(error) => {
+                    assert!(matches!(error, IntervalError::Empty));
+                    assert!(expected.is_empty());
+                }
+            }
+        }
+
+        assert_eq!(Interval::half_open(2, 5).unwrap(), Interval::new(2, 4));
+        assert_eq!(Interval::open(2, 5).unwrap(), Interval::new(3, 4));
+        assert!(Interval::half_open(5, 5).is_err());
+        assert!(Interval::open(4, 5).is_err());
+        assert_eq!(Interval::at_least(3u8), Interval::new(3, u8::MAX));
+        assert_eq!(Interval::greater_than(3u8).unwrap(), Interval::new(4, u8::MAX));
+        assert!(Interval::greater_than(u8::MAX).is_err());
+        assert_eq!(Interval::at_most(3u8), Interval::new(0, 3));
+        assert_eq!(Interval::less_than(3u8).unwrap(), Interval::new(0, 2));
+        assert!(Interval::less_than(u8::MIN).is_err());
+        assert_eq!(Interval::<u8>::unbounded(), Interval::new(0, u8::MAX));
+
+        // Half-open intervals sharing an endpoint are adjacent
+        let mut tree: AVLIntervalTree<u32> = AVLIntervalTree::empty();
+        tree.insert(Interval::half_open(0, 10).unwrap());
+        tree.insert(Interval::half_open(10, 20).unwrap());
+        assert_eq!(tree.iter().collect::<Vec<_>>(), vec![Interval::half_open(0, 20).unwrap()]);
+        tree.delete(Interval::open(4, 8).unwrap());
+        assert!(tree.contains_value(4) && tree.contains_value(8) && !tree.contains_value(5));
+        tree.delete(Interval::at_least(15));
+        assert_eq!(tree.last(), Some(Interval::half_open(8, 15).unwrap()));
+    }
+
+    #[test]
+    fn test_interval_range_conversions() {
+        let interval: Interval<i32> = (3..=7).into();
+        assert_eq!(interval, Interval::new(3, 7));
+        assert_eq!(Interval::try_from(3..8).unwrap(), interval);
+        assert!(matches!(Interval::try_from(3..3), Err(IntervalError::Empty)));
+        assert_eq!(RangeInclusive::from(interval), 3..=7);
+        assert!(interval.contains(&7) && !interval.contains(&8));
+        assert_eq!((interval.start_bound(), interval.end_bound()), (Bound::Included(&3), Bound::Included(&7)));
+
+        let mut tree: AVLIntervalTree<i32> = AVLIntervalTree::empty();
+        tree.insert(0..=9);
+        tree.insert(interval);
+        tree.delete(4..=5);
+        tree.delete(Interval::new(9, 9));
+        assert_eq!(tree.insert_reporting(0..=9), vec![Interval::new(4, 5), Interval::new(9, 9)]);
+        assert_eq!(tree.delete_reporting(8..=20), vec![Interval::new(8, 9)]);
+        assert_eq!(tree.range(interval).collect::<Vec<_>>(), vec![Interval::new(0, 7)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_interval_from_inverted_range() {
+        let (start, stop) = (25, 5);
+        let mut tree: AVLIntervalTree<i32> = AVLIntervalTree::empty();
+        tree.insert(start..=stop);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_interval_from_exhausted_range() {
+        let mut range = 1..=3;
+        assert_eq!(range.by_ref().count(), 3);
+        let _: Interval<i32> = range.into();
+    }
+
+    #[test]
+    fn test_discrete_std_types() {
+        let mut tree: AVLIntervalTree<char> = AVLIntervalTree::empty();
+        tree.insert('a'..='z');
+        tree.insert('\u{D700}'..='\u{D7FF}');
+        tree.insert('\u{E000}'..='\u{E0FF}');
+        assert_eq!(tree.number_of_nodes(), 2);
+        assert_eq!(tree.covered_len(), 26 + 0x100 + 0x100);
+        assert_eq!(tree.select(26 + 0x100), Some('\u{E000}'));
+        assert_eq!(tree.values().nth(26 + 0xFF), Some('\u{D7FF}'));
+        assert_eq!(tree.values().len(), 26 + 0x100 + 0x100);
+        assert_eq!(tree.complement().covered_len(), 0x110000 - 0x800 - tree.covered_len());
+        assert_eq!('\u{D7FF}'.successor(), Some('\u{E000}'));
+        assert_eq!('\u{E000}'.predecessor(), Some('\u{D7FF}'));
+        assert_eq!(char::MAX.successor(), None);
+
+        let mut tree: AVLIntervalTree<Ipv4Addr> = AVLIntervalTree::empty();
+        tree.insert(Ipv4Addr::new(10, 0, 0, 0)..=Ipv4Addr::new(10, 0, 0, 255));
+        tree.insert(Ipv4Addr::new(10, 0, 1, 0)..=Ipv4Addr::new(10, 0, 1, 255));
+        assert_eq!(tree.first(), Some(Interval::new(Ipv4Addr::new(10, 0, 0, 0), Ipv4Addr::new(10, 0, 1, 255))));
+        assert_eq!(tree.covered_len(), 512);
+        assert_eq!(tree.values().len(), 512);
+
+        let mut tree: AVLIntervalTree<Ipv6Addr> = AVLIntervalTree::empty();
+        tree.insert(Interval::unbounded());
+        tree.delete_value(Ipv6Addr::UNSPECIFIED);
+        assert_eq!(tree.first().map(|i| i.start()), Some(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1)));
+        assert_eq!(tree.covered_len(), u128::MAX);
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    struct UserId(u32);
+
+    impl Discrete for UserId {
+        const MIN: Self = UserId(u32::MIN);
+        const MAX: Self = UserId(u32::MAX);
+
+        fn successor(&self) -> Option<Self> {
+            self.0.successor().map(UserId)
+        }
+
+        fn predecessor(&self) -> Option<Self> {
+            self.0.predecessor().map(UserId)
+        }
+
+        fn steps_between(start: &Self, stop: &Self) -> u128 {
+            u32::steps_between(&start.0, &stop.0)
+        }
+
+        fn forward(&self, n: u128) -> Option<Self> {
+            self.0.forward(n).map(UserId)
+        }
+    }
+
+    #[test]
+    fn test_discrete_newtype() {
+        let mut tree: AVLIntervalTree<UserId> = AVLIntervalTree::empty();
+        tree.insert(UserId(1)..=UserId(3));
+        tree.insert_value(UserId(4));
+        assert_eq!(tree.iter().collect::<Vec<_>>(), vec![Interval::new(UserId(1), UserId(4))]);
+        assert_eq!(tree.gaps().next(), Some(Interval::new(UserId(0), UserId(0))));
+        assert!(format!("{tree:?}").contains("UserId(4)"));
+    }
+
+    /// Reference model: the integer points covered by a continuous set over `i8`.
+    fn covered_points(set: &ContinuousIntervalSet<i8>) -> HashSet<i8> {
+        (i8::MIN..=i8::MAX).filter(|&x| set.contains_value(x)).collect()
+    }
+
+    #[test]
+    fn random_test_continuous_set() {
+        let mut set: ContinuousIntervalSet<i8> = ContinuousIntervalSet::empty();
+        let mut items_in_set: HashSet<i8> = HashSet::new();
+        let mut rng = thread_rng();
+        const ITERATIONS: i32 = 1000;
+        for _ in 0..ITERATIONS {
+            let interval = random_interval_small(&mut rng);
+            let Ok(interval) = ContinuousInterval::try_new(interval.start(), interval.stop()) else {
+                continue;
+            };
+            if rng.gen_bool(0.6) {
+                items_in_set.extend(interval.start()..interval.end());
+                set.insert(interval);
+            } else {
+                for x in interval.start()..interval.end() {
+                    items_in_set.remove(&x);
+                }
+                set.delete(interval);
+            }
+            assert_eq!(covered_points(&set), items_in_set);
+            // Stored intervals are disjoint and do not touch
+            let intervals: Vec<ContinuousInterval<i8>> = set.iter().collect();
+            for pair in intervals.windows(2) {
+                assert!(pair[0].end() < pair[1].start());
+            }
+            let probe = ContinuousInterval::new(interval.start(), interval.end());
+            let expected: Vec<_> = intervals.iter()
+                .copied()
+                .filter(|other| other.overlaps_with(&probe))
+                .collect();
+            assert_eq!(set.overlapping(&probe).collect::<Vec<_>>(), expected);
+            assert_eq!(
+                set.contains(&probe),
+                (probe.start()..probe.end()).all(|x| items_in_set.contains(&x))
+            );
+        }
+    }
+
+    /// Seconds as `f64`, totally ordered for use in a `ContinuousIntervalSet`.
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    struct Seconds(f64);
+
+    impl Eq for Seconds {}
+
+    impl PartialOrd for Seconds {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Seconds {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            self.0.total_cmp(&other.0)
+        }
+    }
+
+    #[test]
+    fn test_continuous_set_f64() {
+        let mut set: ContinuousIntervalSet<Seconds> = ContinuousIntervalSet::empty();
+        set.insert(ContinuousInterval::new(Seconds(0.0), Seconds(1.5)));
+        set.insert(Seconds(1.5)..Seconds(2.0));
+        set.insert(Seconds(2.25)..Seconds(3.0));
+        assert_eq!(set.number_of_intervals(), 2);
+        assert!(set.contains_value(Seconds(1.999)));
+        assert!(!set.contains_value(Seconds(2.0)));
+        assert!(!set.contains_value(Seconds(2.1)));
+        assert_eq!(
+            set.find_containing(Seconds(1.0)),
+            Some(ContinuousInterval::new(Seconds(0.0), Seconds(2.0)))
+        );
+
+        set.delete(Seconds(0.5)..Seconds(0.75));
+        assert!(set.contains_value(Seconds(0.75)) && !set.contains_value(Seconds(0.5)));
+        assert_eq!(set.number_of_intervals(), 3);
+        assert_eq!((&set).into_iter().rev().count(), 3);
+        assert!(matches!(
+            ContinuousInterval::try_new(Seconds(1.0), Seconds(1.0)),
+            Err(IntervalError::Empty)
+        ));
+
+        let mut set: ContinuousIntervalSet<i32> = ContinuousIntervalSet::empty();
+        set.insert(ContinuousInterval::new(0, 2));
+        set.insert(ContinuousInterval::new(3, 4));
+        assert_eq!(set.to_string(), "{[0, 2), [3, 4)}");
+    }
+
+    /// Reference model: the value mapped to every key of an interval map over `i8`.
+    fn mapped_points(map: &AVLIntervalMap<i8, u8>) -> HashMap<i8, u8> {
+        map.iter()
+            .flat_map(|(interval, &value)| (interval.start()..=interval.stop()).map(move |x| (x, value)))
+            .collect()
+    }
+
+    #[test]
+    fn random_test_interval_map() {
+        let mut map: AVLIntervalMap<i8, u8> = AVLIntervalMap::empty();
+        let mut items_in_map: HashMap<i8, u8> = HashMap::new();
+        let mut rng = thread_rng();
+        const ITERATIONS: i32 = 1000;
+        for _ in 0..ITERATIONS {
+            let interval = random_interval_small(&mut rng);
+            if rng.gen_bool(0.7) {
+                // Few distinct values, so that neighbours often coalesce
+                let value = rng.gen_range(0..3);
+                items_in_map.extend((interval.start()..=interval.stop()).map(|x| (x, value)));
+                map.insert(interval, value);
+            } else {
+                for x in interval.start()..=interval.stop() {
+                    items_in_map.remove(&x);
+                }
+                map.remove(interval);
+            }
+            assert!(map.is_avl());
+            assert_eq!(mapped_points(&map), items_in_map);
+            // Stored intervals are disjoint, and only adjacent if their values differ
+            let entries: Vec<(Interval<i8>, u8)> = map.iter()
+                .map(|(interval, &value)| (interval, value))
+                .collect();
+            for pair in entries.windows(2) {
+                let ((a, a_value), (b, b_value)) = (pair[0], pair[1]);
+                assert!(a.is_left_of(&b));
+                assert!(!a.left_adjacent_to(&b) || a_value != b_value);
+            }
+            assert_eq!(map.number_of_nodes() as usize, entries.len());
+            let mut reversed: Vec<_> = map.iter().rev().map(|(interval, _)| interval).collect();
+            reversed.reverse();
+            assert!(reversed.iter().eq(entries.iter().map(|(interval, _)| interval)));
+            let x = rng.gen::<i8>();
+            assert_eq!(map.get(x), items_in_map.get(&x));
+        }
+    }
+
+    #[test]
+    fn test_interval_map_tenants() {
+        let mut owners: AVLIntervalMap<Ipv4Addr, &str> = AVLIntervalMap::empty();
+        owners.insert(Ipv4Addr::new(10, 0, 0, 0)..=Ipv4Addr::new(10, 0, 0, 255), "alpha");
+        owners.insert(Ipv4Addr::new(10, 0, 1, 0)..=Ipv4Addr::new(10, 0, 1, 255), "alpha");
+        owners.insert(Ipv4Addr::new(10, 0, 2, 0)..=Ipv4Addr::new(10, 0, 2, 255), "beta");
+        assert_eq!(owners.number_of_nodes(), 2);
+        assert_eq!(owners.get(Ipv4Addr::new(10, 0, 1, 7)), Some(&"alpha"));
+        assert_eq!(owners.get(Ipv4Addr::new(10, 0, 3, 0)), None);
+
+        // Overwriting the middle of a range splits it
+        owners.insert(Ipv4Addr::new(10, 0, 0, 128)..=Ipv4Addr::new(10, 0, 0, 191), "gamma");
+        assert_eq!(
+            owners.iter().map(|(interval, &owner)| (interval.to_string(), owner)).collect::<Vec<_>>(),
+            vec![
+                ("[10.0.0.0, 10.0.0.127]".to_string(), "alpha"),
+                ("[10.0.0.128, 10.0.0.191]".to_string(), "gamma"),
+                ("[10.0.0.192, 10.0.1.255]".to_string(), "alpha"),
+                ("[10.0.2.0, 10.0.2.255]".to_string(), "beta"),
+            ]
+        );
+
+        // Giving it back to the neighbours' owner coalesces the ranges again
+        owners.insert(Ipv4Addr::new(10, 0, 0, 128)..=Ipv4Addr::new(10, 0, 0, 191), "alpha");
+        assert_eq!(
+            owners.get_key_value(Ipv4Addr::new(10, 0, 0, 1)),
+            Some((Interval::new(Ipv4Addr::new(10, 0, 0, 0), Ipv4Addr::new(10, 0, 1, 255)), &"alpha"))
+        );
+
+        owners.remove(Ipv4Addr::new(10, 0, 1, 0)..=Ipv4Addr::new(10, 0, 2, 127));
+        assert!(!owners.contains_key(Ipv4Addr::new(10, 0, 1, 0)));
+        assert_eq!(owners.get(Ipv4Addr::new(10, 0, 2, 128)), Some(&"beta"));
+        assert_eq!(owners.number_of_nodes(), 2);
+    }
+
+    /// Map value counting how often it has been cloned.
+    #[derive(Debug)]
+    struct CloneCounter {
+        id: u8,
+        clones: Rc<Cell<usize>>
+    }
+
+    impl Clone for CloneCounter {
+        fn clone(&self) -> Self {
+            self.clones.set(self.clones.get() + 1);
+            Self{id: self.id, clones: Rc::clone(&self.clones)}
+        }
+    }
+
+    impl PartialEq for CloneCounter {
+        fn eq(&self, other: &Self) -> bool {
+            self.id == other.id
+        }
+    }
+
+    #[test]
+    fn test_interval_map_moves_values() {
+        let clones = Rc::new(Cell::new(0));
+        let value = |id| CloneCounter{id, clones: Rc::clone(&clones)};
+        let mut map: AVLIntervalMap<i32, CloneCounter> = AVLIntervalMap::empty();
+        for i in 0..20 {
+            map.insert(Interval::new(10 * i, 10 * i + 4), value(i as u8 % 2));
+        }
+        // Absorbing neighbours and deleting whole entries moves the values
+        map.insert(Interval::new(5, 9), value(0));
+        map.insert(Interval::new(15, 19), value(5));
+        map.remove(Interval::new(40, 129));
+        assert_eq!(map.number_of_nodes(), 12);
+        assert_eq!(clones.get(), 0);
+
+        // Only splitting an entry into two remainders clones its value
+        map.remove(Interval::new(2, 3));
+        assert_eq!(map.get(1).map(|value| value.id), Some(0));
+        assert_eq!(map.get(4).map(|value| value.id), Some(0));
+        assert_eq!(clones.get(), 2);
+    }
+
     // #[test]
     // fn it_works() -> Result<(), Box<dyn Error>> {
     //     let mut tree = AVLIntervalTree::empty();
@@ -1,6 +1,7 @@
+use crate::discrete::Discrete;
 use crate::interval::Interval;
 
-pub trait IntervalTree<T: num::PrimInt + std::fmt::Display> {
+pub trait IntervalTree<T: Discrete> {
 
     /// Create and return a new empty tree.
     fn empty() -> Self;
@@ -13,11 +14,21 @@ pub trait IntervalTree<T: num::PrimInt + std::fmt::Display> {
     fn number_of_nodes(&self) -> i32;
 
     /// Insert an interval into the tree.
-    fn insert(&mut self, interval: Interval<T>);
+    fn insert<I: Into<Interval<T>>>(&mut self, interval: I);
+
+
+    /// Insert an interval into the tree, and return the
+    /// sub-intervals which were not covered before, in ascending order.
+    fn insert_reporting<I: Into<Interval<T>>>(&mut self, interval: I) -> Vec<Interval<T>>;
 
 
     /// Delete an interval from the tree.
-    fn delete(&mut self, interval: &Interval<T>);
+    fn delete<I: Into<Interval<T>>>(&mut self, interval: I);
+
+
+    /// Delete an interval from the tree, and return the
+    /// sub-intervals which were actually removed, in ascending order.
+    fn delete_reporting<I: Into<Interval<T>>>(&mut self, interval: I) -> Vec<Interval<T>>;
 
 
     /// Check whether an interval is contained in the tree.
@@ -32,7 +43,7 @@ pub trait IntervalTree<T: num::PrimInt + std::fmt::Display> {
 
     /// Delete a single value from the tree.
     fn delete_value(&mut self, value: T) {
-        self.delete(&Interval::new(value, value))
+        self.delete(Interval::new(value, value))
     }
 
     /// Check whether a single value is contained in the tree.